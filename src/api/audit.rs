@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::ApiResponse;
+use crate::database::audit_log::{self, AuditQuery, Model as AuditRecord};
+use crate::error::AppResult;
+use crate::middleware::request_id::current_request_id;
+use crate::middleware::require_permission;
+use crate::state::AppState;
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditParams {
+    actor_id: Option<i32>,
+    action: Option<String>,
+    target_type: Option<String>,
+    target_id: Option<String>,
+    outcome: Option<String>,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditList {
+    items: Vec<AuditRecord>,
+    total: u64,
+}
+
+pub fn create_router(state: AppState) -> Router<AppState> {
+    Router::new().route(
+        "/audit",
+        get(list_audit_events).route_layer(from_fn_with_state(state, require_permission("audit:read"))),
+    )
+}
+
+// 管理员按操作者、动作、目标和时间范围查询审计日志，最新的在前
+async fn list_audit_events(
+    State(state): State<AppState>,
+    Query(params): Query<AuditParams>,
+) -> AppResult<Json<ApiResponse<AuditList>>> {
+    let query = AuditQuery {
+        actor_id: params.actor_id,
+        action: params.action,
+        target_type: params.target_type,
+        target_id: params.target_id,
+        outcome: params.outcome,
+        from: params.from,
+        to: params.to,
+        limit: params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+        offset: params.offset.unwrap_or(0),
+    };
+    let (items, total) = audit_log::query(&state.db, &query).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        message: "Success".to_string(),
+        data: Some(AuditList { items, total }),
+        request_id: current_request_id(),
+    }))
+}
@@ -0,0 +1,50 @@
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+
+use crate::api::ApiResponse;
+use crate::middleware::request_id::current_request_id;
+use crate::health::{ComponentReport, HealthReport, Status};
+use crate::state::AppState;
+
+pub fn create_router() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+        .route("/health", get(health))
+}
+
+fn probe_response<T>(ok: bool, message: &str, data: Option<T>) -> (StatusCode, Json<ApiResponse<T>>) {
+    let status = if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(ApiResponse {
+        code: status.as_u16(),
+        message: message.to_string(),
+        data,
+        request_id: current_request_id(),
+    }))
+}
+
+// 存活探针：进程能处理请求即可，不检查依赖
+pub async fn liveness() -> (StatusCode, Json<ApiResponse<()>>) {
+    probe_response(true, "ok", None)
+}
+
+// 就绪探针：停机中或任一关键依赖不可用时返回 503
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ApiResponse<Vec<ComponentReport>>>) {
+    if !state.ready.is_ready() {
+        return probe_response(false, "服务正在停止", None);
+    }
+
+    let checks = state.health.run(true).await;
+    let failed: Vec<ComponentReport> = checks.into_iter().filter(|c| c.status == Status::Down).collect();
+    if failed.is_empty() {
+        probe_response(true, "ready", None)
+    } else {
+        probe_response(false, "依赖不可用", Some(failed))
+    }
+}
+
+// 运维用的详细报告，包含每个组件的状态和耗时
+pub async fn health(State(state): State<AppState>) -> (StatusCode, Json<ApiResponse<HealthReport>>) {
+    let report = state.health.report(state.ready.is_ready()).await;
+    let ok = report.status == Status::Up;
+    probe_response(ok, if ok { "ok" } else { "degraded" }, Some(report))
+}
@@ -1,19 +1,113 @@
 use axum::{
     Router,
-    routing::post
+    middleware::{from_fn, from_fn_with_state},
+    routing::{get, post}
 };
 
-use crate::database::mysql_orm;
+use serde::{Deserialize, Serialize};
 
+use crate::metrics;
+use crate::middleware::{auth_middleware, idempotency_middleware, request_id::request_id_middleware};
+use crate::middleware::rate_limit::{rate_limit, RateLimit, RateLimitPolicy};
+use crate::shutdown;
+use crate::state::AppState;
+
+pub mod audit;
+pub mod health;
+pub mod precondition;
+pub mod token;
 pub mod user;
+pub mod validation;
+
+#[cfg(test)]
+pub(crate) mod tests;
 
-pub fn create_public_router() -> Router {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiResponse<T> {
+    pub code: u16,
+    pub message: String,
+    pub data: Option<T>,
+    // 与响应头 X-Request-Id 相同，便于按请求排查日志
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+// 完整的应用路由：公开路由 + 需要认证的私有路由，外层依次是连接排空、HTTP 指标和请求ID
+pub fn create_app(state: AppState) -> Router {
+    // 幂等中间件在各路由内部、限流之后挂载，私有路由按用户隔离 Idempotency-Key
+    let public_router = create_public_router(state.clone());
+    let private_router =
+        create_private_router(state.clone()).layer(from_fn_with_state(state.clone(), auth_middleware));
+    public_router
+        .merge(private_router)
+        .layer(from_fn_with_state(state, shutdown::drain_connections))
+        .layer(from_fn(metrics::track_http))
+        .layer(from_fn(request_id_middleware))
+}
+
+pub fn create_public_router(state: AppState) -> Router {
+    let login_limit = RateLimitPolicy {
+        name: "login",
+        ip: Some(RateLimit::per_minute(20)),
+        account: Some(RateLimit::per_minute(5)),
+        subject: None,
+    }
+    .with_overrides(&state.config.rate_limit);
+    let register_limit = RateLimitPolicy {
+        name: "register",
+        ip: Some(RateLimit::per_minute(5)),
+        ..Default::default()
+    }
+    .with_overrides(&state.config.rate_limit);
+    let token_limit = RateLimitPolicy {
+        name: "token",
+        ip: Some(RateLimit::per_minute(30)),
+        ..Default::default()
+    }
+    .with_overrides(&state.config.rate_limit);
+
+    // 登录和令牌接口的响应包含凭据，不挂幂等中间件
     Router::new()
-        .route("/login", post(user::login))
-        .route("/users", post(user::create_user))
+        .route(
+            "/login",
+            post(user::login).route_layer(from_fn_with_state(state.clone(), rate_limit(login_limit))),
+        )
+        .route(
+            "/users",
+            post(user::create_user)
+                .route_layer(from_fn_with_state(state.clone(), idempotency_middleware))
+                .route_layer(from_fn_with_state(state.clone(), rate_limit(register_limit))),
+        )
+        .route(
+            "/token/refresh",
+            post(token::refresh).route_layer(from_fn_with_state(state.clone(), rate_limit(token_limit.clone()))),
+        )
+        .route(
+            "/logout",
+            post(token::logout).route_layer(from_fn_with_state(state.clone(), rate_limit(token_limit))),
+        )
+        .route("/.well-known/jwks.json", get(token::jwks))
+        .route("/metrics", get(metrics::render))
+        .merge(health::create_router())
+        .with_state(state)
 }
 
-pub fn create_private_router() -> Router {
+pub fn create_private_router(state: AppState) -> Router {
+    let api_limit = RateLimitPolicy {
+        name: "api",
+        subject: Some(RateLimit::per_minute(120)),
+        ..Default::default()
+    }
+    .with_overrides(&state.config.rate_limit);
+
     Router::new()
-        .nest("/api", user::create_router())
-}
\ No newline at end of file
+        .nest(
+            "/api",
+            user::create_router(state.clone())
+                .merge(token::create_router(state.clone()))
+                .merge(audit::create_router(state.clone())),
+        )
+        .route_layer(from_fn_with_state(state.clone(), idempotency_middleware))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit(api_limit)))
+        .with_state(state)
+}
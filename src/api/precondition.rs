@@ -0,0 +1,88 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::Response,
+};
+
+use crate::error::{AppError, AppResult};
+
+// 资源版本号对应的强 ETag
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+fn entity_tags(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|t| !t.is_empty())
+}
+
+// 修改类请求必须携带 If-Match，且与当前版本一致（强比较），"*" 匹配任意版本
+pub fn check_if_match(headers: &HeaderMap, version: i32) -> AppResult<()> {
+    let value = headers
+        .get(header::IF_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| AppError::PreconditionRequired("缺少If-Match请求头".to_string()))?;
+
+    let current = etag(version);
+    if value.trim() == "*" || entity_tags(value).any(|tag| tag == current) {
+        Ok(())
+    } else {
+        Err(AppError::PreconditionFailed("资源已被修改，请重新获取后再试".to_string()))
+    }
+}
+
+// If-None-Match 命中当前版本时可直接返回 304（弱比较）
+pub fn is_not_modified(headers: &HeaderMap, version: i32) -> bool {
+    let Some(value) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let current = etag(version);
+    value.trim() == "*"
+        || entity_tags(value).any(|tag| tag.strip_prefix("W/").unwrap_or(tag) == current)
+}
+
+pub fn with_etag(mut response: Response, version: i32) -> Response {
+    if let Ok(value) = HeaderValue::from_str(&etag(version)) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn if_match_requires_header() {
+        let result = check_if_match(&HeaderMap::new(), 1);
+        assert!(matches!(result, Err(AppError::PreconditionRequired(_))));
+    }
+
+    #[test]
+    fn if_match_uses_strong_comparison() {
+        assert!(check_if_match(&headers(header::IF_MATCH, "\"3\""), 3).is_ok());
+        assert!(check_if_match(&headers(header::IF_MATCH, "\"1\", \"3\""), 3).is_ok());
+        assert!(check_if_match(&headers(header::IF_MATCH, "*"), 3).is_ok());
+        assert!(matches!(
+            check_if_match(&headers(header::IF_MATCH, "W/\"3\""), 3),
+            Err(AppError::PreconditionFailed(_))
+        ));
+        assert!(matches!(
+            check_if_match(&headers(header::IF_MATCH, "\"2\""), 3),
+            Err(AppError::PreconditionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        assert!(is_not_modified(&headers(header::IF_NONE_MATCH, "W/\"3\""), 3));
+        assert!(is_not_modified(&headers(header::IF_NONE_MATCH, "\"1\", \"3\""), 3));
+        assert!(is_not_modified(&headers(header::IF_NONE_MATCH, "*"), 3));
+        assert!(!is_not_modified(&headers(header::IF_NONE_MATCH, "\"2\""), 3));
+        assert!(!is_not_modified(&HeaderMap::new(), 3));
+    }
+}
@@ -0,0 +1,315 @@
+// 基于内存 SQLite 的端到端测试，覆盖注册、登录、查询、部分更新、删除和刷新令牌
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, EntityTrait};
+use sea_orm::sea_query::Expr;
+use sea_orm_migration::MigratorTrait;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use crate::audit::{AuditLog, AuditWriter};
+use crate::config::Config;
+use crate::database::audit_log::{self, AuditQuery};
+use crate::database::{mysql_orm, refresh_token, revoked_token};
+use crate::middleware::jwt_keys::JwtKeys;
+use crate::migration::Migrator;
+use crate::password::BcryptHasher;
+use crate::state::AppState;
+
+const PASSWORD: &str = "Passw0rd!x";
+
+// 其他模块的测试也用它构造完整的 AppState
+pub(crate) async fn test_state() -> (AppState, DatabaseConnection, AuditWriter) {
+    // 内存库每个连接各自独立，连接池只保留一个连接
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1).min_connections(1).sqlx_logging(false);
+    let db = Database::connect(options).await.unwrap();
+    Migrator::up(&db, None).await.unwrap();
+
+    let mut config = Config::default();
+    config.jwt.secret = "integration-test-secret-0123456789abcdef".to_string();
+    let jwt = JwtKeys::from_config(&config.jwt).unwrap();
+    let (audit, writer) = AuditLog::from_config(&config.audit, db.clone()).unwrap();
+    let hasher = std::sync::Arc::new(BcryptHasher { cost: 4 });
+    (AppState::new(config, db.clone(), hasher, jwt, audit), db, writer)
+}
+
+async fn setup() -> (Router, DatabaseConnection, AuditWriter) {
+    let (state, db, writer) = test_state().await;
+    (crate::api::create_app(state), db, writer)
+}
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    headers: &[(header::HeaderName, &str)],
+    body: Option<Value>,
+) -> (StatusCode, HeaderMap, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    if body.is_some() {
+        request = request.header(header::CONTENT_TYPE, "application/json");
+    }
+    for (name, value) in headers {
+        request = request.header(name, *value);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty);
+
+    let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+    let (parts, body) = response.into_parts();
+    let bytes = to_bytes(body, usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (parts.status, parts.headers, json)
+}
+
+async fn login(app: &Router, email: &str, password: &str) -> (StatusCode, Value) {
+    let body = json!({ "email": email, "password": password });
+    let (status, _, json) = send(app, Method::POST, "/login", None, &[], Some(body)).await;
+    (status, json)
+}
+
+// 注册并登录，返回用户 id 和登录响应中的令牌对
+async fn register(app: &Router, email: &str) -> (i64, Value) {
+    let body = json!({ "name": "user", "email": email, "password": PASSWORD });
+    let (status, _, created) = send(app, Method::POST, "/users", None, &[], Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", created);
+    let (status, tokens) = login(app, email, PASSWORD).await;
+    assert_eq!(status, StatusCode::OK, "{}", tokens);
+    (created["data"]["id"].as_i64().unwrap(), tokens["data"].clone())
+}
+
+async fn refresh(app: &Router, refresh_token: &Value) -> (StatusCode, Value) {
+    let body = json!({ "refresh_token": refresh_token });
+    let (status, _, json) = send(app, Method::POST, "/token/refresh", None, &[], Some(body)).await;
+    (status, json)
+}
+
+fn etag(headers: &HeaderMap) -> String {
+    headers[header::ETAG].to_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn user_lifecycle() {
+    let (app, db, audit_writer) = setup().await;
+
+    let body = json!({ "name": "alice", "email": "Alice@Example.com", "password": PASSWORD });
+    let (status, _, created) = send(&app, Method::POST, "/users", None, &[], Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", created);
+    let id = created["data"]["id"].as_i64().unwrap();
+    assert_eq!(created["data"]["email"], "alice@example.com");
+
+    // 邮箱不存在与密码错误的响应一致
+    let (wrong, wrong_body) = login(&app, "alice@example.com", "Wrong0rd!x").await;
+    let (unknown, unknown_body) = login(&app, "nobody@example.com", PASSWORD).await;
+    assert_eq!((wrong, unknown), (StatusCode::UNAUTHORIZED, StatusCode::UNAUTHORIZED));
+    assert_eq!(wrong_body["message"], unknown_body["message"]);
+
+    let (status, tokens) = login(&app, "alice@example.com", PASSWORD).await;
+    assert_eq!(status, StatusCode::OK, "{}", tokens);
+    let token = tokens["data"]["access_token"].as_str().unwrap().to_string();
+
+    let uri = format!("/api/users/{}", id);
+    let (status, _, _) = send(&app, Method::GET, &uri, None, &[], None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, headers, user) = send(&app, Method::GET, &uri, Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK, "{}", user);
+    assert_eq!(user["data"]["name"], "alice");
+    let version = etag(&headers);
+    let (status, _, _) = send(&app, Method::GET, &uri, Some(&token), &[(header::IF_NONE_MATCH, &version)], None).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    let patch = json!({ "name": "alice2" });
+    let (status, _, _) = send(&app, Method::PATCH, &uri, Some(&token), &[], Some(patch.clone())).await;
+    assert_eq!(status, StatusCode::PRECONDITION_REQUIRED);
+    let (status, headers, patched) =
+        send(&app, Method::PATCH, &uri, Some(&token), &[(header::IF_MATCH, &version)], Some(patch.clone())).await;
+    assert_eq!(status, StatusCode::OK, "{}", patched);
+    assert_eq!(patched["data"]["name"], "alice2");
+    let (status, _, _) = send(&app, Method::PATCH, &uri, Some(&token), &[(header::IF_MATCH, &version)], Some(patch)).await;
+    assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+    let version = etag(&headers);
+
+    // 普通用户没有 users:delete 权限，提升为管理员后重新登录
+    let (status, _, _) = send(&app, Method::DELETE, &uri, Some(&token), &[(header::IF_MATCH, &version)], None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    mysql_orm::Entity::update_many()
+        .col_expr(mysql_orm::Column::Role, Expr::value("admin"))
+        .exec(&db)
+        .await
+        .unwrap();
+    let (_, tokens) = login(&app, "alice@example.com", PASSWORD).await;
+    let token = tokens["data"]["access_token"].as_str().unwrap().to_string();
+
+    // 查询参数中的通配符按字面量匹配
+    let (status, _, list) = send(&app, Method::GET, "/api/users?name=%25", Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK, "{}", list);
+    assert_eq!(list["data"]["total"], 0);
+    let (_, _, list) = send(&app, Method::GET, "/api/users?name=ice", Some(&token), &[], None).await;
+    assert_eq!(list["data"]["total"], 1);
+
+    let (status, _, deleted) = send(&app, Method::DELETE, &uri, Some(&token), &[(header::IF_MATCH, &version)], None).await;
+    assert_eq!(status, StatusCode::OK, "{}", deleted);
+    let (status, _, _) = send(&app, Method::GET, &uri, Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = login(&app, "alice@example.com", PASSWORD).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 审计事件由后台写入，停机时写完队列
+    audit_writer.shutdown().await;
+    let query = AuditQuery {
+        target_id: Some(id.to_string()),
+        limit: 100,
+        ..Default::default()
+    };
+    let (events, _) = audit_log::query(&db, &query).await.unwrap();
+    let actions: Vec<_> = events.iter().rev().map(|e| (e.action.as_str(), e.outcome.as_str())).collect();
+    assert_eq!(
+        actions,
+        [
+            ("user.create", "success"),
+            ("user.login", "failure"),
+            ("user.login", "success"),
+            ("user.update", "failure"),
+            ("user.update", "success"),
+            ("user.update", "failure"),
+            ("user.login", "success"),
+            ("user.delete", "success"),
+        ]
+    );
+}
+
+
+#[tokio::test]
+async fn refresh_tokens_rotate_and_detect_reuse() {
+    let (app, _db, _audit_writer) = setup().await;
+    let (_, first) = register(&app, "bob@example.com").await;
+
+    let (status, rotated) = refresh(&app, &first["refresh_token"]).await;
+    assert_eq!(status, StatusCode::OK, "{}", rotated);
+    let second = &rotated["data"];
+    assert_ne!(second["refresh_token"], first["refresh_token"]);
+
+    // 已轮换的令牌被重放，整个 family 都被撤销，包括刚轮换出来的新令牌
+    let (status, _) = refresh(&app, &first["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = refresh(&app, &second["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 其他登录会话不受影响
+    let (_, other) = login(&app, "bob@example.com", PASSWORD).await;
+    let (status, _) = refresh(&app, &other["data"]["refresh_token"]).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn expired_refresh_token_is_rejected() {
+    let (app, db, _audit_writer) = setup().await;
+    let (_, tokens) = register(&app, "carol@example.com").await;
+
+    refresh_token::Entity::update_many()
+        .col_expr(refresh_token::Column::ExpiresAt, Expr::value(chrono::Utc::now() - chrono::Duration::seconds(1)))
+        .exec(&db)
+        .await
+        .unwrap();
+    let (status, body) = refresh(&app, &tokens["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["message"], "refresh token已过期");
+
+    // 过期记录由清理任务删除
+    assert_eq!(refresh_token::purge_expired(&db).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn logout_revokes_refresh_and_access_tokens() {
+    let (app, _db, _audit_writer) = setup().await;
+    let (id, tokens) = register(&app, "dave@example.com").await;
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let uri = format!("/api/users/{}", id);
+    let (status, _, _) = send(&app, Method::GET, &uri, Some(access_token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let body = json!({ "refresh_token": tokens["refresh_token"] });
+    let (status, _, json) = send(&app, Method::POST, "/logout", Some(access_token), &[], Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+
+    let (status, _) = refresh(&app, &tokens["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = send(&app, Method::GET, &uri, Some(access_token), &[], None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn change_password_checks_version_and_is_audited() {
+    let (app, db, audit_writer) = setup().await;
+    let (id, tokens) = register(&app, "erin@example.com").await;
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let uri = format!("/api/users/{}", id);
+    let (_, headers, _) = send(&app, Method::GET, &uri, Some(access_token), &[], None).await;
+    let version = etag(&headers);
+
+    let password_uri = format!("{}/password", uri);
+    let body = json!({ "current_password": PASSWORD, "new_password": "N3wPassw0rd!" });
+    let (status, _, _) = send(&app, Method::PUT, &password_uri, Some(access_token), &[], Some(body.clone())).await;
+    assert_eq!(status, StatusCode::PRECONDITION_REQUIRED);
+    let (status, headers, json) =
+        send(&app, Method::PUT, &password_uri, Some(access_token), &[(header::IF_MATCH, &version)], Some(body.clone())).await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    assert_ne!(etag(&headers), version);
+
+    // 旧版本号的修改被拒绝，旧刷新令牌已撤销
+    let (status, _, _) =
+        send(&app, Method::PUT, &password_uri, Some(access_token), &[(header::IF_MATCH, &version)], Some(body)).await;
+    assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+    let (status, _) = refresh(&app, &tokens["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = login(&app, "erin@example.com", "N3wPassw0rd!").await;
+    assert_eq!(status, StatusCode::OK);
+
+    audit_writer.shutdown().await;
+    let query = AuditQuery {
+        action: Some("user.password_change".to_string()),
+        limit: 100,
+        ..Default::default()
+    };
+    let (events, _) = audit_log::query(&db, &query).await.unwrap();
+    let outcomes: Vec<_> = events.iter().rev().map(|e| e.outcome.as_str()).collect();
+    assert_eq!(outcomes, ["failure", "success", "failure"]);
+}
+
+#[tokio::test]
+async fn revoked_tokens_and_sessions_are_rejected() {
+    let (app, db, _audit_writer) = setup().await;
+    let (id, first) = register(&app, "frank@example.com").await;
+    let uri = format!("/api/users/{}", id);
+    let get = |token: Value| {
+        let (app, uri) = (app.clone(), uri.clone());
+        async move { send(&app, Method::GET, &uri, token.as_str(), &[], None).await.0 }
+    };
+
+    // 按 jti 吊销单个访问令牌，同一会话刷新出的新令牌不受影响
+    let payload = first["access_token"].as_str().unwrap().split('.').nth(1).unwrap();
+    let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+    revoked_token::revoke(&db, claims["jti"].as_str().unwrap(), None, expires_at).await.unwrap();
+    assert_eq!(get(first["access_token"].clone()).await, StatusCode::UNAUTHORIZED);
+    let (_, rotated) = refresh(&app, &first["refresh_token"]).await;
+    let second = &rotated["data"];
+    assert_eq!(get(second["access_token"].clone()).await, StatusCode::OK);
+
+    // 只带刷新令牌注销，整个会话（sid）下的访问令牌都失效，其他会话不受影响
+    let (_, other) = login(&app, "frank@example.com", PASSWORD).await;
+    let body = json!({ "refresh_token": second["refresh_token"] });
+    let (status, _, _) = send(&app, Method::POST, "/logout", None, &[], Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(get(second["access_token"].clone()).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(get(other["data"]["access_token"].clone()).await, StatusCode::OK);
+}
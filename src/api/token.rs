@@ -0,0 +1,184 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::ApiResponse;
+use crate::config::JwtConfig;
+use crate::middleware::request_id::current_request_id;
+use crate::database::mysql_orm::Model as DbUser;
+use crate::database::{refresh_token, revoked_token};
+use crate::error::{AppError, AppResult};
+use crate::middleware::auth::{decode_claims, extract_token, generate_token};
+use crate::middleware::jwt_keys::JwkSet;
+use crate::middleware::{require_permission, AuthUser};
+use crate::state::AppState;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenPair {
+    access_token: String,
+    refresh_token: String,
+    token_type: String,
+    expires_in: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevokeAccessToken {
+    jti: String,
+}
+
+pub fn create_router(state: AppState) -> Router<AppState> {
+    Router::new().route(
+        "/tokens/revoke",
+        post(revoke_access_token).route_layer(from_fn_with_state(state, require_permission("tokens:revoke"))),
+    )
+}
+
+// 签发访问令牌和刷新令牌，family_id 为空时开启新的登录会话
+pub async fn issue_token_pair(
+    state: &AppState,
+    user: &DbUser,
+    family_id: Option<String>,
+) -> AppResult<TokenPair> {
+    let config = &state.config.jwt;
+    let family_id = family_id.unwrap_or_else(refresh_token::new_family_id);
+    let access_token = generate_token(&state.jwt, config, user.id, vec![user.role.clone()], Some(family_id.clone()))
+        .map_err(|_| AppError::Internal("Token生成失败".to_string()))?;
+    let refresh_token = refresh_token::issue(
+        &state.db,
+        user.id,
+        Some(family_id),
+        chrono::Duration::seconds(config.refresh_ttl_secs),
+    )
+    .await?;
+
+    Ok(TokenPair {
+        access_token,
+        refresh_token,
+        token_type: "Bearer".to_string(),
+        expires_in: config.access_ttl_secs,
+    })
+}
+
+// 令牌响应禁止缓存 (RFC 6749 5.1)，幂等中间件也据此跳过保存
+pub fn token_response(message: &str, pair: TokenPair) -> Response {
+    let body = Json(ApiResponse {
+        code: 200,
+        message: message.to_string(),
+        data: Some(pair),
+        request_id: current_request_id(),
+    });
+    ([(header::CACHE_CONTROL, "no-store")], body).into_response()
+}
+
+// 访问令牌最晚的失效时间，不知道具体 exp 时按它保留吊销记录
+fn access_token_horizon(config: &JwtConfig) -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now() + chrono::Duration::seconds(config.access_ttl_secs + config.leeway_secs as i64)
+}
+
+// 撤销整个登录会话：刷新令牌族作废，同一 sid 下尚未过期的访问令牌也一并吊销
+async fn revoke_session(state: &AppState, record: &refresh_token::Model) -> AppResult<u64> {
+    let revoked = refresh_token::revoke_family(&state.db, &record.family_id).await?;
+    let expires_at = access_token_horizon(&state.config.jwt);
+    revoked_token::revoke(&state.db, &record.family_id, Some(record.user_id), expires_at).await?;
+    Ok(revoked)
+}
+
+// 轮换刷新令牌；已轮换过的令牌再次出现视为泄露，撤销整个会话
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> AppResult<Response> {
+    let invalid = || AppError::Unauthorized("无效的refresh token".to_string());
+
+    let record = refresh_token::find_by_token(&state.db, &payload.refresh_token)
+        .await?
+        .ok_or_else(invalid)?;
+
+    if record.revoked_at.is_some() || !refresh_token::revoke(&state.db, record.id).await? {
+        let revoked = revoke_session(&state, &record).await?;
+        tracing::warn!(
+            user_id = record.user_id,
+            family_id = %record.family_id,
+            revoked,
+            "检测到refresh token重放，已撤销整个令牌族"
+        );
+        return Err(invalid());
+    }
+
+    if record.expires_at <= chrono::Utc::now() {
+        return Err(AppError::Unauthorized("refresh token已过期".to_string()));
+    }
+
+    // 重新读取用户，角色变更在刷新后生效
+    let user = state.users.find_user_by_id(record.user_id)
+        .await?
+        .ok_or_else(invalid)?;
+    let pair = issue_token_pair(&state, &user, Some(record.family_id)).await?;
+
+    Ok(token_response("刷新成功", pair))
+}
+
+// 公开的验签公钥集合，供其他服务校验访问令牌
+pub async fn jwks(State(state): State<AppState>) -> Json<JwkSet> {
+    Json(state.jwt.jwks().clone())
+}
+
+// 注销：撤销该刷新令牌所在的整个会话；携带访问令牌时一并吊销
+pub async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RefreshRequest>,
+) -> AppResult<Json<ApiResponse<()>>> {
+    if let Some(record) = refresh_token::find_by_token(&state.db, &payload.refresh_token).await? {
+        revoke_session(&state, &record).await?;
+    }
+
+    if let Some(claims) = extract_token(&headers).and_then(|token| decode_claims(&state.jwt, &state.config.jwt, &token).ok()) {
+        revoked_token::revoke(&state.db, &claims.jti, Some(claims.sub), expires_at(claims.exp)).await?;
+    }
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        message: "已退出登录".to_string(),
+        data: None,
+        request_id: current_request_id(),
+    }))
+}
+
+fn expires_at(exp: usize) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(exp as i64, 0).unwrap_or_else(chrono::Utc::now)
+}
+
+// 按 jti 吊销单个访问令牌（例如令牌泄露），在其过期前立即失效
+pub async fn revoke_access_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<RevokeAccessToken>,
+) -> AppResult<Json<ApiResponse<()>>> {
+    let jti = payload.jti.trim();
+    if jti.is_empty() || jti.len() > 64 {
+        return Err(AppError::Validation("无效的jti".to_string()));
+    }
+
+    // jti 不携带过期时间，按访问令牌的最长有效期保留吊销记录
+    revoked_token::revoke(&state.db, jti, None, access_token_horizon(&state.config.jwt)).await?;
+    tracing::info!(jti, revoked_by = auth_user.id, "访问令牌已吊销");
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        message: "令牌已吊销".to_string(),
+        data: None,
+        request_id: current_request_id(),
+    }))
+}
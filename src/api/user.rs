@@ -1,20 +1,36 @@
 use axum::{
-    extract::{State, Path},
-    http::StatusCode,
-    routing::{get, post, put, delete},
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Response},
+    routing::{get, patch, post, put, delete},
     Router,
     Json,
-    Extension
 };
 use serde::{Deserialize, Serialize};
-use crate::database::mysql_orm::{self, Model as DbUser};
-use crate::middleware::auth_middleware;
+use validator::Validate;
+use crate::api::precondition::{check_if_match, is_not_modified, with_etag};
+use crate::api::validation::{field_errors, validate_password_strength, ValidatedJson};
+use crate::api::ApiResponse;
+use crate::api::token::{issue_token_pair, token_response};
+use crate::audit::{AuditEvent, RequestMeta};
+use crate::database::mysql_orm::{self, Cursor, Model as DbUser, SortField, SortOrder, UserQuery};
+use crate::database::refresh_token;
+use crate::error::{AppError, AppResult};
+use crate::metrics::METRICS;
+use crate::password;
+use crate::middleware::rate_limit::too_many_requests;
+use crate::middleware::request_id::current_request_id;
+use crate::middleware::{rbac::ensure_owner_or, require_permission, AuthUser};
+use crate::state::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct User {
     id: Option<i32>,
     name: String,
     email: String,
+    role: String,
 }
 
 impl From<DbUser> for User {
@@ -23,209 +39,502 @@ impl From<DbUser> for User {
             id: Some(db_user.id),
             name: db_user.name,
             email: db_user.email,
+            role: db_user.role,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateUser {
+    #[validate(length(min = 1, max = 50, message = "用户名长度需为1-50个字符"))]
     name: String,
+    #[validate(email(message = "邮箱格式不正确"), length(max = 255, message = "邮箱过长"))]
     email: String,
+    #[validate(custom(function = "validate_password_strength"))]
     password: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// 可修改的资料字段，PUT 整体替换，PATCH 在其 JSON 表示上应用补丁
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUser {
+    #[validate(length(min = 1, max = 50, message = "用户名长度需为1-50个字符"))]
+    name: String,
+    #[validate(email(message = "邮箱格式不正确"), length(max = 255, message = "邮箱过长"))]
+    email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ChangePassword {
+    #[validate(length(min = 1, max = 128, message = "当前密码不能为空"))]
+    current_password: String,
+    #[validate(custom(function = "validate_password_strength"))]
+    new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUsersParams {
+    name: Option<String>,
+    email: Option<String>,
+    created_from: Option<chrono::DateTime<chrono::Utc>>,
+    created_to: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    sort: SortField,
+    #[serde(default)]
+    order: SortOrder,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserList {
+    items: Vec<User>,
+    total: u64,
+    next_cursor: Option<String>,
+    has_more: bool,
+}
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct LoginRequest {
+    #[validate(email(message = "邮箱格式不正确"))]
     email: String,
+    #[validate(length(min = 1, max = 128, message = "密码不能为空"))]
     password: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ApiResponse<T> {
-    code: u16,
-    message: String,
-    data: Option<T>,
+struct LoginFailure {
+    reason: &'static str,
+    user_id: Option<i32>,
+    error: AppError,
 }
 
-pub async fn login(
-    Json(payload): Json<LoginRequest>,
-) -> Result<Json<ApiResponse<String>>, Json<ApiResponse<()>>> {
-    let db = mysql_orm::establish_connection()
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("数据库连接失败: {}", e),
-                data: None,
-            })
-        })?;
-
-    let user = mysql_orm::find_user_by_email(&db, &payload.email)
+impl LoginFailure {
+    fn new(reason: &'static str, user_id: Option<i32>, error: AppError) -> Self {
+        LoginFailure { reason, user_id, error }
+    }
+}
+
+// 校验锁定状态和密码，失败时带上原因供指标和审计使用
+async fn authenticate(state: &AppState, account: &str, payload: &LoginRequest) -> Result<DbUser, LoginFailure> {
+    if let Some(remaining) = state.rate_limiter.lockout_remaining(account).await {
+        return Err(LoginFailure::new(
+            "locked",
+            None,
+            too_many_requests(remaining, "密码错误次数过多，账号已被临时锁定"),
+        ));
+    }
+
+    let user = state.users.find_user_by_email(&payload.email)
         .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("用户查询失败: {}", e),
-                data: None,
-            })
-        })?
-        .ok_or(Json(ApiResponse {
-            code: 404,
-            message: "用户不存在".to_string(),
-            data: None,
-        }))?;
-
-    // if !mysql_orm::verify_password(&user.password, &payload.password) {
-    //     return Err(Json(ApiResponse {
-    //         code: 401,
-    //         message: "密码错误".to_string(),
-    //         data: None,
-    //     }));
-    // }
-
-    let token = crate::middleware::auth::generate_token(user.id)
-        .map_err(|_| Json(ApiResponse {
-            code: 500,
-            message: "Token生成失败".to_string(),
-            data: None,
-        }))?;
+        .map_err(|e| LoginFailure::new("error", None, e.into()))?;
 
-    Ok(Json(ApiResponse {
-        code: 200,
-        message: "登录成功".to_string(),
-        data: Some(token),
-    }))
+    // 邮箱不存在与密码错误返回相同的响应，并同样做一次哈希校验，避免据此探测已注册的邮箱
+    let stored_hash = user.as_ref().map_or(&*state.dummy_hash, |u| u.password.as_str());
+    let verified = mysql_orm::verify_password(stored_hash, &payload.password).await;
+    match user {
+        Some(user) if verified => Ok(user),
+        user => {
+            let user_id = user.map(|u| u.id);
+            if let Some(lockout) = state.rate_limiter.record_failure(account, &state.lockout).await {
+                tracing::warn!(user_id, lockout_secs = lockout.as_secs(), "密码错误次数过多，锁定账号");
+            }
+            let reason = if user_id.is_some() { "bad_password" } else { "unknown_user" };
+            Err(LoginFailure::new(
+                reason,
+                user_id,
+                AppError::Unauthorized("邮箱或密码错误".to_string()),
+            ))
+        }
+    }
 }
 
-pub fn create_router() -> Router {
+pub async fn login(
+    State(state): State<AppState>,
+    meta: RequestMeta,
+    ValidatedJson(payload): ValidatedJson<LoginRequest>,
+) -> AppResult<Response> {
+    let account = mysql_orm::normalize_email(&payload.email);
+    let event = AuditEvent::new("user.login", &meta).actor_label(&account);
+
+    let user = match authenticate(&state, &account, &payload).await {
+        Ok(user) => user,
+        Err(failure) => {
+            METRICS.login("failure", failure.reason);
+            let mut event = event.failure(failure.reason);
+            if let Some(user_id) = failure.user_id {
+                event = event.actor(user_id).target("user", user_id);
+            }
+            state.audit.record(event).await;
+            return Err(failure.error);
+        }
+    };
+    state.rate_limiter.reset_failures(&account).await;
+
+    // 旧算法或旧参数生成的哈希，在登录成功时透明升级
+    if state.hasher.needs_rehash(&user.password) {
+        match password::hash_blocking(state.hasher.clone(), payload.password.clone()).await {
+            Ok(new_hash) => {
+                if let Err(e) = state.users.update_password_hash(user.id, new_hash, user.version).await {
+                    tracing::warn!(user_id = user.id, error = %e, "密码哈希升级失败");
+                }
+            }
+            Err(e) => tracing::warn!(user_id = user.id, error = %e, "密码重新哈希失败"),
+        }
+    }
+
+    let tokens = issue_token_pair(&state, &user, None).await?;
+    METRICS.login("success", "ok");
+    state.audit.record(event.actor(user.id).target("user", user.id)).await;
+
+    Ok(token_response("登录成功", tokens))
+}
+
+pub fn create_router(state: AppState) -> Router<AppState> {
     Router::new()
-        .route("/users/:id", get(get_user).route_layer(axum::middleware::from_fn(auth_middleware)))
-        .route("/users/:id", put(update_user))
-        .route("/users/:id", delete(delete_user))
+        .route(
+            "/users",
+            get(list_users).route_layer(from_fn_with_state(state.clone(), require_permission("users:list"))),
+        )
+        .route(
+            "/users/:id",
+            get(get_user).route_layer(from_fn_with_state(state.clone(), require_permission("users:read"))),
+        )
+        .route(
+            "/users/:id",
+            put(update_user).route_layer(from_fn_with_state(state.clone(), require_permission("users:update"))),
+        )
+        .route(
+            "/users/:id",
+            patch(patch_user).route_layer(from_fn_with_state(state.clone(), require_permission("users:update"))),
+        )
+        .route(
+            "/users/:id/password",
+            put(change_password).route_layer(from_fn_with_state(state.clone(), require_permission("users:update"))),
+        )
+        .route(
+            "/users/:id",
+            delete(delete_user).route_layer(from_fn_with_state(state.clone(), require_permission("users:delete"))),
+        )
+        .route(
+            "/users/:id/restore",
+            post(restore_user).route_layer(from_fn_with_state(state, require_permission("users:restore"))),
+        )
 }
 
 pub async fn create_user(
-    Json(payload): Json<CreateUser>,
-) -> Result<Json<ApiResponse<User>>, Json<ApiResponse<()>>> {
-    let db = mysql_orm::establish_connection()
+    State(state): State<AppState>,
+    meta: RequestMeta,
+    ValidatedJson(payload): ValidatedJson<CreateUser>,
+) -> AppResult<(StatusCode, Json<ApiResponse<User>>)> {
+    let account = mysql_orm::normalize_email(&payload.email);
+    let result = state.users
+        .create_user(state.hasher.clone(), payload.name, payload.email, payload.password)
         .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("创建用户连接失败: {}", e),
-                data: None,
-            })
-        })?;
-
-    let db_user = mysql_orm::create_user(&db, payload.name, payload.email, payload.password)
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("创建用户操作失败: {}", e),
-                data: None,
-            })
-        })?;
+        .map_err(AppError::from);
 
-    Ok(Json(ApiResponse {
+    let mut event = AuditEvent::new("user.create", &meta).actor_label(&account).outcome(&result);
+    if let Ok(db_user) = &result {
+        event = event.target("user", db_user.id).changes(None, Some(audit_snapshot(db_user)));
+    }
+    state.audit.record(event).await;
+    let db_user = result?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse {
         code: 201,
         message: "Success".to_string(),
         data: Some(User::from(db_user)),
-    }))
+        request_id: current_request_id(),
+    })))
 }
 
-async fn get_user(
-    Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<User>>, Json<ApiResponse<()>>> {
-    let db = mysql_orm::establish_connection()
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("获取用户连接失败: {}", e),
-                data: None,
-            })
-        })?;
-
-    let db_user = mysql_orm::find_user_by_id(&db, id)
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("获取用户操作失败: {}", e),
-                data: None,
-            })
-        })?
-        .ok_or(Json(ApiResponse {
-            code: 404,
-            message: "用户未找到".to_string(),
-            data: None,
-        }))?;
+async fn list_users(
+    State(state): State<AppState>,
+    Query(params): Query<ListUsersParams>,
+) -> AppResult<Json<ApiResponse<UserList>>> {
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|raw| Cursor::decode(raw, params.sort).ok_or_else(|| AppError::Validation("无效的游标".to_string())))
+        .transpose()?;
+
+    let query = UserQuery {
+        name: params.name,
+        email: params.email,
+        created_from: params.created_from,
+        created_to: params.created_to,
+        sort: params.sort,
+        order: params.order,
+        limit: params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+        offset: params.offset,
+        cursor,
+    };
+    let page = state.users.list_users(&query).await?;
 
     Ok(Json(ApiResponse {
         code: 200,
         message: "Success".to_string(),
-        data: Some(User::from(db_user)),
+        data: Some(UserList {
+            items: page.items.into_iter().map(User::from).collect(),
+            total: page.total,
+            next_cursor: page.next_cursor,
+            has_more: page.has_more,
+        }),
+        request_id: current_request_id(),
     }))
 }
 
-async fn update_user(
-    Path(id): Path<i32>,
-    Json(payload): Json<CreateUser>,
-) -> Result<Json<ApiResponse<User>>, Json<ApiResponse<()>>> {
-    let db = mysql_orm::establish_connection()
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("更新用户连接失败: {}", e),
-                data: None,
-            })
-        })?;
-
-    let db_user = mysql_orm::update_user(&db, id, Some(payload.name), Some(payload.email))
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("更新用户操作失败: {}", e),
-                data: None,
-            })
-        })?;
+// 审计记录中的用户快照，不包含密码哈希
+fn audit_snapshot(user: &DbUser) -> serde_json::Value {
+    serde_json::json!({
+        "name": user.name,
+        "email": user.email,
+        "role": user.role,
+    })
+}
 
-    Ok(Json(ApiResponse {
+// 记录用户资料更新的审计事件，成功时附带变更前后的差异
+async fn audit_update(state: &AppState, event: AuditEvent, result: &AppResult<(DbUser, DbUser)>) {
+    let mut event = event.outcome(result);
+    if let Ok((before, after)) = result {
+        event = event.changes(Some(audit_snapshot(before)), Some(audit_snapshot(after)));
+    }
+    state.audit.record(event).await;
+}
+
+// 单个用户的响应，带上版本号对应的 ETag
+fn user_response(db_user: DbUser) -> Response {
+    let version = db_user.version;
+    let body = Json(ApiResponse {
         code: 200,
         message: "Success".to_string(),
         data: Some(User::from(db_user)),
-    }))
+        request_id: current_request_id(),
+    });
+    with_etag(body.into_response(), version)
+}
+
+async fn find_existing(state: &AppState, id: i32) -> AppResult<DbUser> {
+    state.users.find_user_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户未找到".to_string()))
+}
+
+async fn get_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    tracing::debug!(caller = auth_user.id, target = id, "查询用户");
+    let db_user = find_existing(&state, id).await?;
+
+    if is_not_modified(&headers, db_user.version) {
+        return Ok(with_etag(StatusCode::NOT_MODIFIED.into_response(), db_user.version));
+    }
+    Ok(user_response(db_user))
+}
+
+async fn update_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    ValidatedJson(payload): ValidatedJson<UpdateUser>,
+) -> AppResult<Response> {
+    tracing::info!(caller = auth_user.id, target = id, "更新用户");
+    let result = async {
+        ensure_owner_or(&state.db, &auth_user, id, "users:manage").await?;
+        let current = find_existing(&state, id).await?;
+        check_if_match(&headers, current.version)?;
+
+        let db_user = state.users.update_user(id, Some(payload.name), Some(payload.email), current.version).await?;
+        Ok((current, db_user))
+    }
+    .await;
+
+    let event = AuditEvent::new("user.update", &meta).actor(auth_user.id).target("user", id);
+    audit_update(&state, event, &result).await;
+    let (_, db_user) = result?;
+    Ok(user_response(db_user))
+}
+
+// 按 Content-Type 应用 JSON Patch (RFC 6902) 或 JSON Merge Patch (RFC 7386)
+fn apply_patch(doc: &mut serde_json::Value, headers: &HeaderMap, body: &[u8]) -> AppResult<()> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match content_type.as_str() {
+        "application/json-patch+json" => {
+            let operations: json_patch::Patch = serde_json::from_slice(body)
+                .map_err(|e| AppError::Validation(format!("JSON Patch格式错误: {}", e)))?;
+            json_patch::patch(doc, &operations)
+                .map_err(|e| AppError::Validation(format!("JSON Patch应用失败: {}", e)))
+        }
+        "application/merge-patch+json" | "application/json" => {
+            let merge: serde_json::Value = serde_json::from_slice(body)
+                .map_err(|e| AppError::Validation(format!("JSON Merge Patch格式错误: {}", e)))?;
+            json_patch::merge(doc, &merge);
+            Ok(())
+        }
+        other => Err(AppError::UnsupportedMediaType(format!("不支持的Content-Type: {}", other))),
+    }
+}
+
+// 部分更新，只修改补丁中实际改变的字段
+async fn patch_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Response> {
+    tracing::info!(caller = auth_user.id, target = id, "部分更新用户");
+    let result = apply_user_patch(&state, &auth_user, id, &headers, &body).await;
+
+    let event = AuditEvent::new("user.update", &meta).actor(auth_user.id).target("user", id);
+    audit_update(&state, event, &result).await;
+    let (_, db_user) = result?;
+    Ok(user_response(db_user))
+}
+
+async fn apply_user_patch(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: i32,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> AppResult<(DbUser, DbUser)> {
+    ensure_owner_or(&state.db, auth_user, id, "users:manage").await?;
+    let current = find_existing(state, id).await?;
+    check_if_match(headers, current.version)?;
+
+    let mut doc = serde_json::to_value(UpdateUser {
+        name: current.name.clone(),
+        email: current.email.clone(),
+    })
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    apply_patch(&mut doc, headers, body)?;
+
+    let patched: UpdateUser = serde_json::from_value(doc)
+        .map_err(|e| AppError::Validation(format!("补丁结果无效: {}", e)))?;
+    patched
+        .validate()
+        .map_err(|e| AppError::InvalidFields(field_errors(&e)))?;
+
+    let name = (patched.name != current.name).then_some(patched.name);
+    let email = (patched.email != current.email).then_some(patched.email);
+    let db_user = if name.is_none() && email.is_none() {
+        current.clone()
+    } else {
+        state.users.update_user(id, name, email, current.version).await?
+    };
+
+    Ok((current, db_user))
+}
+
+// 修改密码，必须由本人提供当前密码并带上 If-Match；成功后撤销该用户的全部刷新令牌
+async fn change_password(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    ValidatedJson(payload): ValidatedJson<ChangePassword>,
+) -> AppResult<Response> {
+    let result = async {
+        if auth_user.id != id {
+            return Err(AppError::Forbidden("只能修改自己的密码".to_string()));
+        }
+        let user = find_existing(&state, id).await?;
+        check_if_match(&headers, user.version)?;
+
+        if !mysql_orm::verify_password(&user.password, &payload.current_password).await {
+            return Err(AppError::Unauthorized("当前密码错误".to_string()));
+        }
+
+        let new_hash = password::hash_blocking(state.hasher.clone(), payload.new_password)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let updated = state.users.update_password_hash(id, new_hash, user.version).await?;
+        refresh_token::revoke_all_for_user(&state.db, id).await?;
+        Ok(updated)
+    }
+    .await;
+
+    // 审计事件不记录密码哈希的变化
+    let event = AuditEvent::new("user.password_change", &meta).actor(auth_user.id).target("user", id).outcome(&result);
+    state.audit.record(event).await;
+    let updated = result?;
+
+    let body = Json(ApiResponse::<()> {
+        code: 200,
+        message: "密码已修改".to_string(),
+        data: None,
+        request_id: current_request_id(),
+    });
+    Ok(with_etag(body.into_response(), updated.version))
 }
 
 async fn delete_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    meta: RequestMeta,
     Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<()>>, Json<ApiResponse<()>>> {
-    let db = mysql_orm::establish_connection()
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("删除用户连接失败: {}", e),
-                data: None,
-            })
-        })?;
-
-    mysql_orm::delete_user(&db, id)
-        .await
-        .map_err(|e| {
-            Json(ApiResponse {
-                code: 500,
-                message: format!("删除用户操作失败: {}", e),
-                data: None,
-            })
-        })?;
+    headers: HeaderMap,
+) -> AppResult<Json<ApiResponse<()>>> {
+    let result = async {
+        ensure_owner_or(&state.db, &auth_user, id, "users:manage").await?;
+        let current = find_existing(&state, id).await?;
+        check_if_match(&headers, current.version)?;
+
+        state.users.delete_user(id, current.version).await?;
+        refresh_token::revoke_all_for_user(&state.db, id).await?;
+        Ok(current)
+    }
+    .await;
+
+    let mut event = AuditEvent::new("user.delete", &meta).actor(auth_user.id).target("user", id).outcome(&result);
+    if let Ok(before) = &result {
+        event = event.changes(Some(audit_snapshot(before)), None);
+    }
+    state.audit.record(event).await;
+    result?;
 
     Ok(Json(ApiResponse {
         code: 200,
         message: "删除成功".to_string(),
         data: None,
+        request_id: current_request_id(),
     }))
-}
\ No newline at end of file
+}
+
+// 管理员恢复软删除的用户
+async fn restore_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i32>,
+) -> AppResult<Response> {
+    tracing::info!(caller = auth_user.id, target = id, "恢复用户");
+    let db_user = state.users.restore_user(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户不存在或未被删除".to_string()))?;
+    let version = db_user.version;
+
+    let body = Json(ApiResponse {
+        code: 200,
+        message: "恢复成功".to_string(),
+        data: Some(User::from(db_user)),
+        request_id: current_request_id(),
+    });
+    Ok(with_etag(body.into_response(), version))
+}
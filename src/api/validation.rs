@@ -0,0 +1,73 @@
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::error::AppError;
+
+// 单个字段的校验错误，前端据此高亮对应表单项
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut list: Vec<FieldError> = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errs)| {
+            errs.iter().map(move |e| FieldError {
+                field: field.to_string(),
+                code: e.code.to_string(),
+                message: e
+                    .message
+                    .as_ref()
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| format!("{} 校验失败: {}", field, e.code)),
+            })
+        })
+        .collect();
+    list.sort_by(|a, b| a.field.cmp(&b.field));
+    list
+}
+
+// 先反序列化 JSON 再执行声明式校验，失败时返回 422 和字段错误列表
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|e: JsonRejection| AppError::Validation(format!("请求体格式错误: {}", e.body_text())))?;
+        value
+            .validate()
+            .map_err(|e| AppError::InvalidFields(field_errors(&e)))?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+// 密码强度策略：8-128 位，至少包含一个字母和一个数字
+pub fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
+    let length = password.chars().count();
+    let has_letter = password.chars().any(|c| c.is_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+    if (8..=128).contains(&length) && has_letter && has_digit {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("password_strength");
+        error.message = Some("密码长度需为8-128位，且同时包含字母和数字".into());
+        Err(error)
+    }
+}
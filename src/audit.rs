@@ -0,0 +1,343 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, Extensions, HeaderMap},
+};
+use sea_orm::{DatabaseConnection, NotSet, Set};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::config::AuditConfig;
+use crate::database::audit_log;
+use crate::error::AppResult;
+use crate::metrics::METRICS;
+use crate::middleware::rate_limit::client_ip;
+use crate::middleware::request_id::current_request_id;
+use crate::state::AppState;
+
+type SinkError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Success,
+    Failure,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Failure => "failure",
+        }
+    }
+}
+
+// 调用方的网络信息，用于审计
+#[derive(Debug, Clone, Default)]
+pub struct RequestMeta {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl RequestMeta {
+    pub fn from_parts(headers: &HeaderMap, extensions: &Extensions, trusted_hops: usize) -> Self {
+        RequestMeta {
+            ip: client_ip(headers, extensions, trusted_hops),
+            user_agent: headers
+                .get(header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| truncate(v, 512)),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for RequestMeta {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        Ok(RequestMeta::from_parts(
+            &parts.headers,
+            &parts.extensions,
+            state.config.rate_limit.trusted_hops(),
+        ))
+    }
+}
+
+// 一条安全相关的审计事件，默认结果为成功
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub actor_id: Option<i32>,
+    pub actor: Option<String>,
+    pub action: &'static str,
+    pub target_type: Option<&'static str>,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub outcome: Outcome,
+    pub reason: Option<String>,
+    pub changes: Option<Value>,
+    pub request_id: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn new(action: &'static str, meta: &RequestMeta) -> Self {
+        AuditEvent {
+            occurred_at: chrono::Utc::now(),
+            actor_id: None,
+            actor: None,
+            action,
+            target_type: None,
+            target_id: None,
+            ip: meta.ip.clone(),
+            user_agent: meta.user_agent.clone(),
+            outcome: Outcome::Success,
+            reason: None,
+            changes: None,
+            request_id: current_request_id(),
+        }
+    }
+
+    pub fn actor(mut self, id: i32) -> Self {
+        self.actor_id = Some(id);
+        self
+    }
+
+    // 无法确定用户ID时的补充标识，如登录使用的邮箱
+    pub fn actor_label(mut self, label: &str) -> Self {
+        self.actor = Some(truncate(label, 255));
+        self
+    }
+
+    pub fn target(mut self, kind: &'static str, id: impl ToString) -> Self {
+        self.target_type = Some(kind);
+        self.target_id = Some(truncate(&id.to_string(), 64));
+        self
+    }
+
+    pub fn failure(mut self, reason: impl ToString) -> Self {
+        self.outcome = Outcome::Failure;
+        self.reason = Some(truncate(&reason.to_string(), 255));
+        self
+    }
+
+    // 处理失败时记录错误信息
+    pub fn outcome<T>(self, result: &AppResult<T>) -> Self {
+        match result {
+            Ok(_) => self,
+            Err(e) => self.failure(e),
+        }
+    }
+
+    // 只记录发生变化的字段，创建时 before 为空，删除时 after 为空
+    pub fn changes(mut self, before: Option<Value>, after: Option<Value>) -> Self {
+        let fields = |v: Option<Value>| match v {
+            Some(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        let (before, after) = (fields(before), fields(after));
+
+        let mut diff = Map::new();
+        for key in before.keys().chain(after.keys()) {
+            let old = before.get(key).unwrap_or(&Value::Null);
+            let new = after.get(key).unwrap_or(&Value::Null);
+            if old != new && !diff.contains_key(key) {
+                diff.insert(key.clone(), json!({ "before": old, "after": new }));
+            }
+        }
+        self.changes = (!diff.is_empty()).then_some(Value::Object(diff));
+        self
+    }
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
+
+// 审计事件的输出目标
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn write(&self, event: &AuditEvent) -> Result<(), SinkError>;
+}
+
+// 写入只追加的 audit_logs 表
+pub struct DatabaseAuditSink {
+    db: DatabaseConnection,
+}
+
+impl DatabaseAuditSink {
+    pub fn new(db: DatabaseConnection) -> Self {
+        DatabaseAuditSink { db }
+    }
+}
+
+#[async_trait]
+impl AuditSink for DatabaseAuditSink {
+    async fn write(&self, event: &AuditEvent) -> Result<(), SinkError> {
+        let record = audit_log::ActiveModel {
+            id: NotSet,
+            occurred_at: Set(event.occurred_at),
+            actor_id: Set(event.actor_id),
+            actor: Set(event.actor.clone()),
+            action: Set(event.action.to_string()),
+            target_type: Set(event.target_type.map(str::to_string)),
+            target_id: Set(event.target_id.clone()),
+            ip: Set(event.ip.clone()),
+            user_agent: Set(event.user_agent.clone()),
+            outcome: Set(event.outcome.as_str().to_string()),
+            reason: Set(event.reason.clone()),
+            changes: Set(event.changes.clone()),
+            request_id: Set(event.request_id.clone()),
+        };
+        audit_log::insert(&self.db, record).await?;
+        Ok(())
+    }
+}
+
+// 每个事件一行 JSON，追加写入文件，便于日志采集
+pub struct JsonLinesAuditSink {
+    file: Mutex<tokio::fs::File>,
+}
+
+impl JsonLinesAuditSink {
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(JsonLinesAuditSink {
+            file: Mutex::new(tokio::fs::File::from_std(file)),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonLinesAuditSink {
+    async fn write(&self, event: &AuditEvent) -> Result<(), SinkError> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+// 请求只把事件放入有界队列，由后台任务依次写入所有输出目标；写入失败只记日志，不影响请求本身
+pub struct AuditLog {
+    sender: mpsc::Sender<AuditEvent>,
+}
+
+impl AuditLog {
+    // 启动后台写入任务，返回的 AuditWriter 用于停机时写完队列中剩余的事件
+    pub fn from_config(config: &AuditConfig, db: DatabaseConnection) -> std::io::Result<(Self, AuditWriter)> {
+        let mut sinks: Vec<Arc<dyn AuditSink>> = vec![Arc::new(DatabaseAuditSink::new(db))];
+        if let Some(path) = &config.file_path {
+            sinks.push(Arc::new(JsonLinesAuditSink::open(path)?));
+        }
+
+        let (sender, receiver) = mpsc::channel(config.queue_capacity);
+        let (close, closed) = oneshot::channel();
+        let handle = tokio::spawn(run_writer(receiver, sinks, closed));
+        Ok((AuditLog { sender }, AuditWriter { close, handle }))
+    }
+
+    // 队列未满时立即返回
+    pub async fn record(&self, event: AuditEvent) {
+        if let Err(mpsc::error::SendError(event)) = self.sender.send(event).await {
+            tracing::error!(action = event.action, "审计日志已关闭，事件未写入");
+        }
+    }
+
+    // 匿名请求也能大量触发的事件（例如认证失败）不等待队列：队列已用一半时直接丢弃并计数，
+    // 给需要审计的业务请求保留余量
+    pub fn try_record(&self, event: AuditEvent) {
+        if self.sender.capacity() * 2 <= self.sender.max_capacity() {
+            METRICS.audit_dropped.with_label_values(&[event.action]).inc();
+            return;
+        }
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                METRICS.audit_dropped.with_label_values(&[event.action]).inc();
+            }
+            Err(mpsc::error::TrySendError::Closed(event)) => {
+                tracing::error!(action = event.action, "审计日志已关闭，事件未写入");
+            }
+        }
+    }
+}
+
+pub struct AuditWriter {
+    close: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl AuditWriter {
+    // 停止接收新事件，写完已入队的事件后返回
+    pub async fn shutdown(self) {
+        let _ = self.close.send(());
+        if let Err(e) = self.handle.await {
+            tracing::error!(error = %e, "审计日志写入任务异常退出");
+        }
+    }
+}
+
+async fn run_writer(
+    mut receiver: mpsc::Receiver<AuditEvent>,
+    sinks: Vec<Arc<dyn AuditSink>>,
+    mut closed: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Some(event) => write_event(&sinks, &event).await,
+                None => return,
+            },
+            Ok(()) = &mut closed => break,
+        }
+    }
+    receiver.close();
+    while let Some(event) = receiver.recv().await {
+        write_event(&sinks, &event).await;
+    }
+}
+
+async fn write_event(sinks: &[Arc<dyn AuditSink>], event: &AuditEvent) {
+    for sink in sinks {
+        if let Err(e) = sink.write(event).await {
+            tracing::error!(error = %e, action = event.action, "写入审计日志失败");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn try_record_drops_once_half_the_queue_is_used() {
+        let (sender, mut receiver) = mpsc::channel(4);
+        let audit = AuditLog { sender };
+        let dropped = METRICS.audit_dropped.with_label_values(&["test.flood"]);
+        let before = dropped.get();
+
+        for _ in 0..4 {
+            audit.try_record(AuditEvent::new("test.flood", &RequestMeta::default()));
+        }
+        assert_eq!(dropped.get() - before, 2);
+
+        // 保留的余量仍可用于需要审计的事件
+        audit.record(AuditEvent::new("user.update", &RequestMeta::default())).await;
+        let mut actions = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            actions.push(event.action);
+        }
+        assert_eq!(actions, ["test.flood", "test.flood", "user.update"]);
+    }
+}
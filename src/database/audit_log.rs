@@ -0,0 +1,84 @@
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+// 审计日志，只追加，不提供修改和删除
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    // 操作者用户ID，匿名请求为空
+    pub actor_id: Option<i32>,
+    // 操作者的补充标识，如登录失败时尝试的邮箱
+    pub actor: Option<String>,
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    // success | failure
+    pub outcome: String,
+    pub reason: Option<String>,
+    // 变更前后的字段差异: {"字段": {"before": .., "after": ..}}
+    pub changes: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub actor_id: Option<i32>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub outcome: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+pub async fn insert(db: &DatabaseConnection, record: ActiveModel) -> Result<(), DbErr> {
+    Entity::insert(record).exec(db).await?;
+    Ok(())
+}
+
+// 按条件分页查询，最新的记录在前
+pub async fn query(db: &DatabaseConnection, query: &AuditQuery) -> Result<(Vec<Model>, u64), DbErr> {
+    let mut select = Entity::find();
+    if let Some(actor_id) = query.actor_id {
+        select = select.filter(Column::ActorId.eq(actor_id));
+    }
+    if let Some(action) = &query.action {
+        select = select.filter(Column::Action.eq(action.as_str()));
+    }
+    if let Some(target_type) = &query.target_type {
+        select = select.filter(Column::TargetType.eq(target_type.as_str()));
+    }
+    if let Some(target_id) = &query.target_id {
+        select = select.filter(Column::TargetId.eq(target_id.as_str()));
+    }
+    if let Some(outcome) = &query.outcome {
+        select = select.filter(Column::Outcome.eq(outcome.as_str()));
+    }
+    if let Some(from) = query.from {
+        select = select.filter(Column::OccurredAt.gte(from));
+    }
+    if let Some(to) = query.to {
+        select = select.filter(Column::OccurredAt.lt(to));
+    }
+
+    let total = select.clone().count(db).await?;
+    let items = select
+        .order_by_desc(Column::Id)
+        .limit(query.limit)
+        .offset(query.offset)
+        .all(db)
+        .await?;
+    Ok((items, total))
+}
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+// 幂等请求记录，status_code 为空表示首个请求仍在处理中
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "idempotency_keys")]
+pub struct Model {
+    // sha256(用户 + 方法 + 路径 + Idempotency-Key)
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub key_hash: String,
+    pub request_hash: String,
+    pub status_code: Option<i32>,
+    pub content_type: Option<String>,
+    pub response_body: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    // 处理中占位的租约，过期后同一请求的重试可以接管
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// 幂等记录存储，默认使用数据库表，可替换为共享缓存等实现
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    async fn find(&self, key_hash: &str) -> Result<Option<Model>, DbErr>;
+
+    // 占位，key 已存在且仍在租约内或已完成时返回 false
+    async fn try_begin(
+        &self,
+        key_hash: &str,
+        request_hash: &str,
+        ttl: chrono::Duration,
+        lease: chrono::Duration,
+    ) -> Result<bool, DbErr>;
+
+    async fn complete(&self, key_hash: &str, status_code: u16, content_type: Option<String>, body: String) -> Result<(), DbErr>;
+
+    // 删除占位，使客户端可以重试
+    async fn release(&self, key_hash: &str) -> Result<(), DbErr>;
+
+    async fn purge_expired(&self) -> Result<u64, DbErr>;
+}
+
+pub struct SeaOrmIdempotencyStore {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmIdempotencyStore {
+    pub fn new(db: DatabaseConnection) -> Self {
+        SeaOrmIdempotencyStore { db }
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for SeaOrmIdempotencyStore {
+    async fn find(&self, key_hash: &str) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(key_hash.to_string()).one(&self.db).await
+    }
+
+    async fn try_begin(
+        &self,
+        key_hash: &str,
+        request_hash: &str,
+        ttl: chrono::Duration,
+        lease: chrono::Duration,
+    ) -> Result<bool, DbErr> {
+        let now = chrono::Utc::now();
+        // 过期记录直接清掉，允许复用 key
+        Entity::delete_many()
+            .filter(Column::KeyHash.eq(key_hash))
+            .filter(Column::ExpiresAt.lte(now))
+            .exec(&self.db)
+            .await?;
+
+        let record = ActiveModel {
+            key_hash: Set(key_hash.to_string()),
+            request_hash: Set(request_hash.to_string()),
+            status_code: Set(None),
+            content_type: Set(None),
+            response_body: Set(None),
+            created_at: Set(now),
+            expires_at: Set(now + ttl),
+            locked_until: Set(Some(now + lease)),
+        };
+        match Entity::insert(record).exec(&self.db).await {
+            Ok(_) => return Ok(true),
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {}
+            Err(e) => return Err(e),
+        }
+
+        // 上一个处理者没有完成也没有释放（进程崩溃等），租约过期后由相同请求接管
+        let res = Entity::update_many()
+            .col_expr(Column::LockedUntil, Expr::value(now + lease))
+            .filter(Column::KeyHash.eq(key_hash))
+            .filter(Column::RequestHash.eq(request_hash))
+            .filter(Column::StatusCode.is_null())
+            .filter(
+                Condition::any()
+                    .add(Column::LockedUntil.is_null())
+                    .add(Column::LockedUntil.lte(now)),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
+
+    async fn complete(&self, key_hash: &str, status_code: u16, content_type: Option<String>, body: String) -> Result<(), DbErr> {
+        let record = ActiveModel {
+            key_hash: Unchanged(key_hash.to_string()),
+            status_code: Set(Some(status_code as i32)),
+            content_type: Set(content_type),
+            response_body: Set(Some(body)),
+            locked_until: Set(None),
+            ..Default::default()
+        };
+        record.update(&self.db).await?;
+        Ok(())
+    }
+
+    async fn release(&self, key_hash: &str) -> Result<(), DbErr> {
+        Entity::delete_by_id(key_hash.to_string()).exec(&self.db).await?;
+        Ok(())
+    }
+
+    async fn purge_expired(&self) -> Result<u64, DbErr> {
+        let res = Entity::delete_many()
+            .filter(Column::ExpiresAt.lte(chrono::Utc::now()))
+            .exec(&self.db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+}
@@ -1,3 +1,10 @@
+pub mod audit_log;
+pub mod idempotency;
 pub mod mysql_orm;
+pub mod refresh_token;
+pub mod repository;
+pub mod revoked_token;
+pub mod role;
 
-pub use mysql_orm::*;
\ No newline at end of file
+pub use idempotency::{IdempotencyStore, SeaOrmIdempotencyStore};
+pub use repository::{SeaOrmUserRepository, UserRepository};
@@ -0,0 +1,113 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto;
+
+// 刷新令牌表，只保存令牌的 SHA-256 哈希
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "refresh_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub user_id: i32,
+    // 同一次登录轮换出来的令牌属于同一个 family
+    pub family_id: String,
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+fn random_token() -> String {
+    URL_SAFE_NO_PAD.encode(crypto::random_bytes::<32>())
+}
+
+// 新登录会话的 family_id，同时作为访问令牌中的 sid
+pub fn new_family_id() -> String {
+    random_token()
+}
+
+pub fn hash_token(token: &str) -> String {
+    crypto::sha256_hex(token.as_bytes())
+}
+
+// 签发新的刷新令牌，family_id 为空时开启新的 family，返回明文令牌
+pub async fn issue(
+    db: &DatabaseConnection,
+    user_id: i32,
+    family_id: Option<String>,
+    ttl: chrono::Duration,
+) -> Result<String, DbErr> {
+    let token = random_token();
+    let now = chrono::Utc::now();
+    let record = ActiveModel {
+        user_id: Set(user_id),
+        family_id: Set(family_id.unwrap_or_else(random_token)),
+        token_hash: Set(hash_token(&token)),
+        expires_at: Set(now + ttl),
+        revoked_at: Set(None),
+        created_at: Set(now),
+        ..Default::default()
+    };
+
+    record.insert(db).await?;
+    Ok(token)
+}
+
+pub async fn find_by_token(db: &DatabaseConnection, token: &str) -> Result<Option<Model>, DbErr> {
+    let record = Entity::find()
+        .filter(Column::TokenHash.eq(hash_token(token)))
+        .one(db)
+        .await?;
+    Ok(record)
+}
+
+// 撤销单个令牌，返回是否由本次调用完成撤销（用于检测并发重放）
+pub async fn revoke(db: &DatabaseConnection, id: i32) -> Result<bool, DbErr> {
+    let res = Entity::update_many()
+        .col_expr(Column::RevokedAt, Expr::value(chrono::Utc::now()))
+        .filter(Column::Id.eq(id))
+        .filter(Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected == 1)
+}
+
+// 撤销整个 family
+pub async fn revoke_family(db: &DatabaseConnection, family_id: &str) -> Result<u64, DbErr> {
+    let res = Entity::update_many()
+        .col_expr(Column::RevokedAt, Expr::value(chrono::Utc::now()))
+        .filter(Column::FamilyId.eq(family_id))
+        .filter(Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+// 删除已过期的令牌。已撤销但未过期的记录保留到过期，用于识别旧令牌被重放
+pub async fn purge_expired(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let res = Entity::delete_many()
+        .filter(Column::ExpiresAt.lte(chrono::Utc::now()))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+// 撤销用户的全部刷新令牌（修改密码后使用）
+pub async fn revoke_all_for_user(db: &DatabaseConnection, user_id: i32) -> Result<u64, DbErr> {
+    let res = Entity::update_many()
+        .col_expr(Column::RevokedAt, Expr::value(chrono::Utc::now()))
+        .filter(Column::UserId.eq(user_id))
+        .filter(Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::database::mysql_orm::{self, Model, UserPage, UserQuery};
+use crate::password::PasswordHasher;
+
+// 用户存储抽象，handler 只依赖该 trait，不关心具体数据库
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn create_user(&self, hasher: Arc<dyn PasswordHasher>, name: String, email: String, password: String) -> Result<Model, DbErr>;
+
+    // 乐观锁更新，版本不一致返回 DbErr::RecordNotUpdated
+    async fn update_user(&self, id: i32, name: Option<String>, email: Option<String>, expected_version: i32) -> Result<Model, DbErr>;
+
+    async fn update_password_hash(&self, id: i32, password_hash: String, expected_version: i32) -> Result<Model, DbErr>;
+
+    // 软删除
+    async fn delete_user(&self, id: i32, expected_version: i32) -> Result<(), DbErr>;
+
+    async fn restore_user(&self, id: i32) -> Result<Option<Model>, DbErr>;
+
+    async fn purge_deleted_users(&self, before: chrono::DateTime<chrono::Utc>) -> Result<u64, DbErr>;
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<Model>, DbErr>;
+
+    async fn find_user_by_id(&self, id: i32) -> Result<Option<Model>, DbErr>;
+
+    async fn list_users(&self, query: &UserQuery) -> Result<UserPage, DbErr>;
+}
+
+// 基于 SeaORM 的实现，MySQL / Postgres / SQLite 通用
+pub struct SeaOrmUserRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmUserRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        SeaOrmUserRepository { db }
+    }
+}
+
+#[async_trait]
+impl UserRepository for SeaOrmUserRepository {
+    async fn create_user(&self, hasher: Arc<dyn PasswordHasher>, name: String, email: String, password: String) -> Result<Model, DbErr> {
+        mysql_orm::create_user(&self.db, hasher, name, email, password).await
+    }
+
+    async fn update_user(&self, id: i32, name: Option<String>, email: Option<String>, expected_version: i32) -> Result<Model, DbErr> {
+        mysql_orm::update_user(&self.db, id, name, email, expected_version).await
+    }
+
+    async fn update_password_hash(&self, id: i32, password_hash: String, expected_version: i32) -> Result<Model, DbErr> {
+        mysql_orm::update_password_hash(&self.db, id, password_hash, expected_version).await
+    }
+
+    async fn delete_user(&self, id: i32, expected_version: i32) -> Result<(), DbErr> {
+        mysql_orm::delete_user(&self.db, id, expected_version).await
+    }
+
+    async fn restore_user(&self, id: i32) -> Result<Option<Model>, DbErr> {
+        mysql_orm::restore_user(&self.db, id).await
+    }
+
+    async fn purge_deleted_users(&self, before: chrono::DateTime<chrono::Utc>) -> Result<u64, DbErr> {
+        mysql_orm::purge_deleted_users(&self.db, before).await
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<Model>, DbErr> {
+        mysql_orm::find_user_by_email(&self.db, email).await
+    }
+
+    async fn find_user_by_id(&self, id: i32) -> Result<Option<Model>, DbErr> {
+        mysql_orm::find_user_by_id(&self.db, id).await
+    }
+
+    async fn list_users(&self, query: &UserQuery) -> Result<UserPage, DbErr> {
+        mysql_orm::list_users(&self.db, query).await
+    }
+}
@@ -0,0 +1,57 @@
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+// 访问令牌吊销列表，按 jti 记录单个令牌，或按会话 sid 记录整个会话，过期后即可清理
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "revoked_tokens")]
+pub struct Model {
+    // 令牌的 jti 或会话的 sid
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub jti: String,
+    pub user_id: Option<i32>,
+    // 令牌本身的过期时间，之后该记录不再需要
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// 吊销单个访问令牌，重复吊销不报错
+pub async fn revoke(
+    db: &DatabaseConnection,
+    jti: &str,
+    user_id: Option<i32>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), DbErr> {
+    let record = ActiveModel {
+        jti: Set(jti.to_string()),
+        user_id: Set(user_id),
+        expires_at: Set(expires_at),
+        revoked_at: Set(chrono::Utc::now()),
+    };
+    match Entity::insert(record).exec(db).await {
+        Ok(_) => Ok(()),
+        Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+// 令牌本身或它所属的会话任一被吊销即视为已吊销
+pub async fn is_revoked(db: &DatabaseConnection, jti: &str, sid: Option<&str>) -> Result<bool, DbErr> {
+    let count = Entity::find()
+        .filter(Column::Jti.is_in(std::iter::once(jti).chain(sid)))
+        .count(db)
+        .await?;
+    Ok(count > 0)
+}
+
+pub async fn purge_expired(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let res = Entity::delete_many()
+        .filter(Column::ExpiresAt.lte(chrono::Utc::now()))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
@@ -0,0 +1,49 @@
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+pub const ADMIN: &str = "admin";
+pub const USER: &str = "user";
+
+// 自定义角色表，permissions 为逗号分隔的权限列表，例如 "users:read,users:delete"
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "roles")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    pub permissions: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn permission_list(&self) -> impl Iterator<Item = &str> {
+        self.permissions
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+    }
+}
+
+// 内置角色的权限，不需要查库
+pub fn builtin_permissions(role: &str) -> Option<&'static [&'static str]> {
+    match role {
+        ADMIN => Some(&["*"]),
+        USER => Some(&["users:read", "users:update"]),
+        _ => None,
+    }
+}
+
+pub async fn find_roles(db: &DatabaseConnection, names: &[String]) -> Result<Vec<Model>, DbErr> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    let roles = Entity::find()
+        .filter(Column::Name.is_in(names.iter().cloned()))
+        .all(db)
+        .await?;
+    Ok(roles)
+}
@@ -0,0 +1,162 @@
+use axum::{http::{header, StatusCode}, response::{IntoResponse, Response}, Json};
+use sea_orm::{DbErr, SqlErr};
+
+use crate::api::{validation::FieldError, ApiResponse};
+use crate::middleware::auth::AuthError;
+use crate::middleware::request_id::current_request_id;
+
+// 统一的应用错误类型，转换为对应的 HTTP 状态码，响应体仍使用 ApiResponse 包装
+#[derive(Debug)]
+pub enum AppError {
+    NotFound(String),
+    // 唯一约束冲突，field 为冲突的字段
+    Conflict { field: String, message: String },
+    Validation(String),
+    // 字段级校验失败，响应 data 中返回字段错误列表
+    InvalidFields(Vec<FieldError>),
+    Unauthorized(String),
+    Forbidden(String),
+    UnsupportedMediaType(String),
+    PreconditionFailed(String),
+    PreconditionRequired(String),
+    // 限流或登录锁定，retry_after 为秒数
+    TooManyRequests { message: String, retry_after: u64 },
+    Database(DbErr),
+    Internal(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Validation(_) | AppError::InvalidFields(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            AppError::NotFound(msg)
+            | AppError::Conflict { message: msg, .. }
+            | AppError::Validation(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::UnsupportedMediaType(msg)
+            | AppError::PreconditionFailed(msg)
+            | AppError::PreconditionRequired(msg)
+            | AppError::TooManyRequests { message: msg, .. }
+            | AppError::Internal(msg) => msg.clone(),
+            AppError::InvalidFields(_) => "请求参数校验失败".to_string(),
+            // 数据库错误的原始信息只写日志，不返回给客户端
+            AppError::Database(_) => "数据库操作失败".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Database(e) => write!(f, "数据库错误: {}", e),
+            other => write!(f, "{}", other.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+// 从各数据库的唯一约束错误信息中识别冲突字段
+// MySQL: Duplicate entry 'a@x.com' for key 'users.uk_users_email'
+// Postgres: duplicate key value violates unique constraint "uk_users_email"
+// SQLite: UNIQUE constraint failed: users.email
+fn conflict_from_unique_violation(detail: &str) -> AppError {
+    let detail = detail.to_lowercase();
+    if detail.contains("email") {
+        AppError::Conflict {
+            field: "email".to_string(),
+            message: "该邮箱已被注册".to_string(),
+        }
+    } else {
+        tracing::warn!(detail = %detail, "未识别的唯一约束冲突");
+        AppError::Conflict {
+            field: "unknown".to_string(),
+            message: "数据已存在".to_string(),
+        }
+    }
+}
+
+impl From<DbErr> for AppError {
+    fn from(e: DbErr) -> Self {
+        if let Some(SqlErr::UniqueConstraintViolation(detail)) = e.sql_err() {
+            return conflict_from_unique_violation(&detail);
+        }
+        match e {
+            DbErr::RecordNotFound(msg) => AppError::NotFound(msg),
+            // 乐观锁条件更新未命中
+            DbErr::RecordNotUpdated => AppError::PreconditionFailed("资源已被修改，请重新获取后再试".to_string()),
+            e => AppError::Database(e),
+        }
+    }
+}
+
+impl From<AuthError> for AppError {
+    fn from(e: AuthError) -> Self {
+        AppError::Unauthorized(e.message)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            tracing::error!(error = %self, "请求处理失败");
+        }
+        let message = self.message();
+        if let AppError::Conflict { field, message } = self {
+            let body = Json(ApiResponse {
+                code: status.as_u16(),
+                message: message.clone(),
+                data: Some(vec![FieldError {
+                    field,
+                    code: "conflict".to_string(),
+                    message,
+                }]),
+                request_id: current_request_id(),
+            });
+            return (status, body).into_response();
+        }
+        if let AppError::TooManyRequests { retry_after, .. } = self {
+            let body = Json(ApiResponse::<()> {
+                code: status.as_u16(),
+                message,
+                data: None,
+                request_id: current_request_id(),
+            });
+            return (status, [(header::RETRY_AFTER, retry_after.to_string())], body).into_response();
+        }
+        if let AppError::InvalidFields(fields) = self {
+            let body = Json(ApiResponse {
+                code: status.as_u16(),
+                message,
+                data: Some(fields),
+                request_id: current_request_id(),
+            });
+            return (status, body).into_response();
+        }
+        let body = Json(ApiResponse::<()> {
+            code: status.as_u16(),
+            message,
+            data: None,
+            request_id: current_request_id(),
+        });
+        (status, body).into_response()
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use sea_orm_migration::MigratorTrait;
+use serde::Serialize;
+
+use crate::migration::Migrator;
+
+// 组件健康检查，新的子系统实现该 trait 并在 AppState::new 中注册
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // 非关键组件失败只体现在 /health 报告中，不影响 /readyz
+    fn critical(&self) -> bool {
+        true
+    }
+
+    async fn check(&self) -> Result<(), String>;
+}
+
+// 通过共享连接池执行一次 ping
+pub struct DatabaseCheck {
+    db: DatabaseConnection,
+}
+
+impl DatabaseCheck {
+    pub fn new(db: DatabaseConnection) -> Self {
+        DatabaseCheck { db }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for DatabaseCheck {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        self.db.ping().await.map_err(|e| e.to_string())
+    }
+}
+
+// 所有迁移均已执行
+pub struct MigrationCheck {
+    db: DatabaseConnection,
+}
+
+impl MigrationCheck {
+    pub fn new(db: DatabaseConnection) -> Self {
+        MigrationCheck { db }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for MigrationCheck {
+    fn name(&self) -> &'static str {
+        "migrations"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let pending = Migrator::get_pending_migrations(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+        if pending.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} 个迁移未执行", pending.len()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComponentReport {
+    pub name: &'static str,
+    pub status: Status,
+    pub critical: bool,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub status: Status,
+    pub ready: bool,
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    pub checks: Vec<ComponentReport>,
+}
+
+pub struct HealthRegistry {
+    checks: Vec<Arc<dyn HealthCheck>>,
+    timeout: Duration,
+    started: Instant,
+}
+
+impl HealthRegistry {
+    pub fn new(timeout: Duration) -> Self {
+        HealthRegistry {
+            checks: Vec::new(),
+            timeout,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn register(&mut self, check: Arc<dyn HealthCheck>) {
+        self.checks.push(check);
+    }
+
+    // 并发执行检查，单个检查超时记为失败；critical_only 用于 /readyz
+    pub async fn run(&self, critical_only: bool) -> Vec<ComponentReport> {
+        let checks = self
+            .checks
+            .iter()
+            .filter(|check| !critical_only || check.critical())
+            .map(|check| async move {
+                let started = Instant::now();
+                let result = match tokio::time::timeout(self.timeout, check.check()).await {
+                    Ok(result) => result,
+                    Err(_) => Err(format!("检查超时 ({}ms)", self.timeout.as_millis())),
+                };
+                ComponentReport {
+                    name: check.name(),
+                    status: if result.is_ok() { Status::Up } else { Status::Down },
+                    critical: check.critical(),
+                    latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+                    error: result.err(),
+                }
+            });
+        futures::future::join_all(checks).await
+    }
+
+    pub async fn report(&self, ready: bool) -> HealthReport {
+        let checks = self.run(false).await;
+        let healthy = checks.iter().all(|c| c.status == Status::Up || !c.critical);
+        HealthReport {
+            status: if healthy { Status::Up } else { Status::Down },
+            ready: ready && healthy,
+            version: env!("CARGO_PKG_VERSION"),
+            uptime_secs: self.started.elapsed().as_secs(),
+            checks,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio::task::JoinHandle;
+
+use crate::config::PurgeConfig;
+use crate::database::{refresh_token, revoked_token, IdempotencyStore, UserRepository};
+use crate::middleware::rate_limit::RateLimitStore;
+use crate::shutdown::Shutdown;
+
+// 按固定间隔执行一次清理，返回删除的行数；name 用于日志区分各个任务
+fn spawn_periodic<F, Fut>(name: &'static str, interval: Duration, shutdown: Shutdown, mut f: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<u64, DbErr>> + Send,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            // 停机时在两次清理之间退出，不打断正在执行的清理
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.wait() => break,
+            }
+            match f().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(job = name, purged, "定时清理完成"),
+                Err(e) => tracing::error!(job = name, error = %e, "定时清理失败"),
+            }
+        }
+    })
+}
+
+// 后台定时永久删除超过保留期的软删除用户
+pub fn spawn_user_purge(users: Arc<dyn UserRepository>, config: &PurgeConfig, shutdown: Shutdown) -> JoinHandle<()> {
+    let retention = chrono::Duration::days(config.user_retention_days);
+    let interval = Duration::from_secs(config.interval_secs);
+    spawn_periodic("deleted_users", interval, shutdown, move || {
+        let users = users.clone();
+        async move { users.purge_deleted_users(chrono::Utc::now() - retention).await }
+    })
+}
+
+// 定时清理过期的幂等记录
+pub fn spawn_idempotency_purge(store: Arc<dyn IdempotencyStore>, interval: Duration, shutdown: Shutdown) -> JoinHandle<()> {
+    spawn_periodic("idempotency_keys", interval, shutdown, move || {
+        let store = store.clone();
+        async move { store.purge_expired().await }
+    })
+}
+
+// 定时清理已过期令牌的吊销记录
+pub fn spawn_revoked_token_purge(db: DatabaseConnection, interval: Duration, shutdown: Shutdown) -> JoinHandle<()> {
+    spawn_periodic("revoked_tokens", interval, shutdown, move || {
+        let db = db.clone();
+        async move { revoked_token::purge_expired(&db).await }
+    })
+}
+
+// 定时清理过期的刷新令牌
+pub fn spawn_refresh_token_purge(db: DatabaseConnection, interval: Duration, shutdown: Shutdown) -> JoinHandle<()> {
+    spawn_periodic("refresh_tokens", interval, shutdown, move || {
+        let db = db.clone();
+        async move { refresh_token::purge_expired(&db).await }
+    })
+}
+
+// 定时清理限流存储中已补满的桶和过期的登录失败计数
+pub fn spawn_rate_limit_purge(store: Arc<dyn RateLimitStore>, interval: Duration, shutdown: Shutdown) -> JoinHandle<()> {
+    spawn_periodic("rate_limits", interval, shutdown, move || {
+        let store = store.clone();
+        async move { Ok(store.purge_expired().await) }
+    })
+}
@@ -1,41 +1,205 @@
+#[allow(dead_code)]
 mod test_func;
 mod api;
+mod audit;
+mod config;
+mod crypto;
 mod database;
+mod error;
+mod health;
+mod jobs;
+mod metrics;
 mod middleware;
+mod migration;
+mod password;
+mod shutdown;
+mod state;
+mod telemetry;
 
+use std::future::IntoFuture;
 use std::net::SocketAddr;
+use std::time::Duration;
+
+use sea_orm_migration::MigratorTrait;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
+
+// 命令行参数: [--config <path>] [--print-config] [migrate up|down|status]
+struct Args {
+    config: Option<String>,
+    print_config: bool,
+    command: Vec<String>,
+}
+
+fn parse_args() -> Args {
+    let mut args = Args {
+        config: None,
+        print_config: false,
+        command: Vec::new(),
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--config" => args.config = iter.next(),
+            "--print-config" => args.print_config = true,
+            _ => match arg.strip_prefix("--config=") {
+                Some(path) => args.config = Some(path.to_string()),
+                None => args.command.push(arg),
+            },
+        }
+    }
+    args
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env()
-            .add_directive("middleware=info".parse()?))
+    let args = parse_args();
+    let config = match config::Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprint!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if args.print_config {
+        print!("{}", config.redacted().to_toml());
+        return Ok(());
+    }
+
+    // 配置中的日志指令在前，RUST_LOG 追加在后
+    let log_filter = match std::env::var("RUST_LOG") {
+        Ok(extra) if !extra.is_empty() => format!("{},{}", config.server.log_filter, extra),
+        _ => config.server.log_filter.clone(),
+    };
+    let fmt_filter = tracing_subscriber::EnvFilter::try_new(&log_filter)?;
+    let fmt_layer = if config.server.log_format == "json" {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_filter(fmt_filter)
+            .boxed()
+    } else {
+        tracing_subscriber::fmt::layer().with_filter(fmt_filter).boxed()
+    };
+    let otel_filter = tracing_subscriber::EnvFilter::try_new(&log_filter)?;
+    let (telemetry, otel_layer) = telemetry::Telemetry::init(&config.telemetry, otel_filter)?;
+    tracing_subscriber::registry()
+        .with(std::iter::once(fmt_layer).chain(otel_layer).collect::<Vec<_>>())
         .init();
-    let public_router = api::create_public_router();
-    let private_router = api::create_private_router()
-        .layer(tower::ServiceBuilder::new()
-            .layer(axum::middleware::from_fn(middleware::auth::auth_middleware)));
-    let app = public_router.merge(private_router);
-    
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    println!("Server running on http://{}", addr);
-
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
 
+    let db = database::mysql_orm::establish_connection(&config.database).await?;
+
+    // migrate up|down|status 子命令，执行完直接退出
+    if args.command.first().map(String::as_str) == Some("migrate") {
+        migration::run_command(&db, args.command.get(1).map(String::as_str)).await?;
+        return Ok(());
+    }
+
+    // database.run_migrations 开启时启动前自动执行未应用的迁移
+    if config.database.run_migrations {
+        migration::Migrator::up(&db, None).await?;
+        tracing::info!("数据库迁移已执行");
+    }
+
+    // 使用默认JWT密钥时拒绝启动，除非显式开启 jwt.allow_dev_secret
+    let jwt_keys = middleware::jwt_keys::JwtKeys::from_config(&config.jwt)?;
+    let hasher = password::hasher_from_config(&config.password)?;
+    let (audit, audit_writer) = audit::AuditLog::from_config(&config.audit, db.clone())?;
+    let state = state::AppState::new(config, db, hasher, jwt_keys, audit);
+
+    let shutdown = shutdown::Shutdown::new();
+    let purge_config = &state.config.purge;
+    let purge_interval = Duration::from_secs(purge_config.interval_secs);
+    let jobs = vec![
+        jobs::purge::spawn_user_purge(state.users.clone(), purge_config, shutdown.clone()),
+        jobs::purge::spawn_idempotency_purge(state.idempotency.clone(), purge_interval, shutdown.clone()),
+        jobs::purge::spawn_revoked_token_purge(state.db.clone(), purge_interval, shutdown.clone()),
+        jobs::purge::spawn_refresh_token_purge(state.db.clone(), purge_interval, shutdown.clone()),
+        jobs::purge::spawn_rate_limit_purge(
+            state.rate_limiter.clone(),
+            middleware::rate_limit::SWEEP_INTERVAL,
+            shutdown.clone(),
+        ),
+    ];
+
+    let server = state.config.server.clone();
+    let db = state.db.clone();
+    let ready = state.ready.clone();
+
+    let app = api::create_app(state);
+
+    let listener = tokio::net::TcpListener::bind((server.host.as_str(), server.port)).await?;
+    tracing::info!(addr = %listener.local_addr()?, "Server running");
+
+    // 收到信号后先标记未就绪，等待 shutdown_delay 让负载均衡摘除实例，再停止接收新连接
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        let ready = ready.clone();
+        let delay = Duration::from_secs(server.shutdown_delay_secs);
+        async move {
+            shutdown::signal().await;
+            ready.set(false);
+            tracing::info!(delay_secs = delay.as_secs(), "收到停机信号，已标记为未就绪");
+            tokio::time::sleep(delay).await;
+            shutdown.trigger();
+        }
+    });
+
+    ready.set(true);
+    let serve = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        })
+        .into_future();
+    let drain_timeout = Duration::from_secs(server.drain_timeout_secs);
+    let drain_deadline = async {
+        shutdown.wait().await;
+        tokio::time::sleep(drain_timeout).await;
+    };
+    let result = tokio::select! {
+        result = serve => result,
+        _ = drain_deadline => {
+            tracing::warn!(timeout_secs = drain_timeout.as_secs(), "等待进行中的请求超时，强制退出");
+            Ok(())
+        }
+    };
+
+    // 服务异常退出时同样需要通知后台任务
+    ready.set(false);
+    shutdown.trigger();
+    for job in jobs {
+        if tokio::time::timeout(drain_timeout, job).await.is_err() {
+            tracing::warn!("后台任务未在超时时间内退出");
+        }
+    }
+    // 数据库连接关闭前写完排队中的审计事件
+    if tokio::time::timeout(drain_timeout, audit_writer.shutdown()).await.is_err() {
+        tracing::warn!("审计日志未在超时时间内写完");
+    }
+    if let Err(e) = db.close().await {
+        tracing::error!(error = %e, "关闭数据库连接池失败");
+    }
+    tracing::info!("服务已停止");
+    telemetry.shutdown();
+
+    result?;
     Ok(())
 }
 
+#[allow(dead_code)]
 async fn test_parallel_sum() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let sum = test_func::parallel_sum::calculate_parallel_sum().await?;
     println!("并发计算1到10万的数字之和: {}", sum);
     Ok(())
 }
 
+#[allow(dead_code)]
 async fn test_file_processor() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     test_func::file_processor::process_file_concurrent("test.txt", "output.txt").await
 }
 
+#[allow(dead_code)]
 fn test_slice() {
     // devlop ---迁出
     // 测试函数实现
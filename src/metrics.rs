@@ -0,0 +1,237 @@
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sea_orm::DatabaseConnection;
+
+use crate::state::AppState;
+
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+const QUERY_BUCKETS: &[f64] = &[0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+// 进程内全局指标，数据库查询回调等没有 AppState 的地方也能直接记录
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_duration: HistogramVec,
+    pub http_in_flight: IntGaugeVec,
+    pub logins: IntCounterVec,
+    pub token_failures: IntCounterVec,
+    pub rate_limited: IntCounterVec,
+    pub audit_dropped: IntCounterVec,
+    pub db_queries: IntCounterVec,
+    pub db_query_duration: HistogramVec,
+    pub db_pool_connections: IntGaugeVec,
+    pub db_pool_max: IntGauge,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        // 指标名和标签都是常量，注册失败说明代码有误
+        fn register<T: prometheus::core::Collector + Clone + 'static>(registry: &Registry, collector: T) -> T {
+            registry.register(Box::new(collector.clone())).expect("重复注册指标");
+            collector
+        }
+
+        Metrics {
+            http_requests: register(&registry, IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP 请求数"),
+                &["method", "route", "status"],
+            ).unwrap()),
+            http_duration: register(&registry, HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP 请求耗时").buckets(LATENCY_BUCKETS.to_vec()),
+                &["method", "route"],
+            ).unwrap()),
+            http_in_flight: register(&registry, IntGaugeVec::new(
+                Opts::new("http_requests_in_flight", "正在处理的 HTTP 请求数"),
+                &["method", "route"],
+            ).unwrap()),
+            logins: register(&registry, IntCounterVec::new(
+                Opts::new("auth_login_total", "登录次数"),
+                &["result", "reason"],
+            ).unwrap()),
+            token_failures: register(&registry, IntCounterVec::new(
+                Opts::new("auth_token_validation_failures_total", "访问令牌校验失败次数"),
+                &["reason"],
+            ).unwrap()),
+            rate_limited: register(&registry, IntCounterVec::new(
+                Opts::new("rate_limit_rejections_total", "被限流拒绝的请求数"),
+                &["policy"],
+            ).unwrap()),
+            audit_dropped: register(&registry, IntCounterVec::new(
+                Opts::new("audit_events_dropped_total", "审计队列繁忙时丢弃的事件数"),
+                &["action"],
+            ).unwrap()),
+            db_queries: register(&registry, IntCounterVec::new(
+                Opts::new("db_queries_total", "数据库查询次数"),
+                &["operation", "table", "result"],
+            ).unwrap()),
+            db_query_duration: register(&registry, HistogramVec::new(
+                HistogramOpts::new("db_query_duration_seconds", "数据库查询耗时").buckets(QUERY_BUCKETS.to_vec()),
+                &["operation", "table"],
+            ).unwrap()),
+            db_pool_connections: register(&registry, IntGaugeVec::new(
+                Opts::new("db_pool_connections", "连接池中的连接数"),
+                &["state"],
+            ).unwrap()),
+            db_pool_max: register(&registry, IntGauge::new("db_pool_max_connections", "连接池最大连接数").unwrap()),
+            registry,
+        }
+    }
+
+    pub fn login(&self, result: &str, reason: &str) {
+        self.logins.with_label_values(&[result, reason]).inc();
+    }
+
+    pub fn token_failure(&self, reason: &str) {
+        self.token_failures.with_label_values(&[reason]).inc();
+    }
+
+    // sea-orm 查询回调，按语句类型和表名统计
+    pub fn record_query(&self, info: &sea_orm::metric::Info<'_>) {
+        let (operation, table) = describe_statement(&info.statement.sql);
+        let result = if info.failed { "error" } else { "ok" };
+        self.db_queries.with_label_values(&[operation, &table, result]).inc();
+        self.db_query_duration
+            .with_label_values(&[operation, &table])
+            .observe(info.elapsed.as_secs_f64());
+    }
+
+    // 连接池状态在抓取时读取
+    fn update_pool(&self, db: &DatabaseConnection) {
+        let Some((size, idle, max)) = pool_stats(db) else {
+            return;
+        };
+        self.db_pool_connections.with_label_values(&["idle"]).set(idle as i64);
+        self.db_pool_connections.with_label_values(&["active"]).set(size.saturating_sub(idle) as i64);
+        self.db_pool_max.set(max as i64);
+    }
+}
+
+fn pool_stats(db: &DatabaseConnection) -> Option<(u32, u32, u32)> {
+    match db {
+        #[cfg(feature = "mysql")]
+        DatabaseConnection::SqlxMySqlPoolConnection(_) => {
+            let pool = db.get_mysql_connection_pool();
+            Some((pool.size(), pool.num_idle() as u32, pool.options().get_max_connections()))
+        }
+        #[cfg(feature = "postgres")]
+        DatabaseConnection::SqlxPostgresPoolConnection(_) => {
+            let pool = db.get_postgres_connection_pool();
+            Some((pool.size(), pool.num_idle() as u32, pool.options().get_max_connections()))
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseConnection::SqlxSqlitePoolConnection(_) => {
+            let pool = db.get_sqlite_connection_pool();
+            Some((pool.size(), pool.num_idle() as u32, pool.options().get_max_connections()))
+        }
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+// 从 SQL 中取出语句类型和主表名，控制标签基数
+fn describe_statement(sql: &str) -> (&'static str, String) {
+    let mut words = sql.split_whitespace();
+    let first = words.next().unwrap_or_default().to_uppercase();
+    let (operation, keyword) = match first.as_str() {
+        "SELECT" => ("select", "FROM"),
+        "INSERT" => ("insert", "INTO"),
+        "UPDATE" => ("update", "UPDATE"),
+        "DELETE" => ("delete", "FROM"),
+        _ => return ("other", String::new()),
+    };
+    let table = if keyword == "UPDATE" {
+        words.next()
+    } else {
+        words.skip_while(|w| !w.eq_ignore_ascii_case(keyword)).nth(1)
+    };
+    let table = match table.unwrap_or_default() {
+        t if t.starts_with('(') => "subquery".to_string(),
+        t => t.trim_matches(|c| c == '"' || c == '`').to_string(),
+    };
+    (operation, table)
+}
+
+// 请求被取消（客户端断开）时也要减少在途计数
+struct InFlight(prometheus::IntGauge);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+// HTTP 指标中间件，放在合并后的路由最外层，按匹配到的路由模板统计
+pub async fn track_http(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let metrics = &*METRICS;
+    let in_flight = InFlight(metrics.http_in_flight.with_label_values(&[&method, &route]));
+    in_flight.0.inc();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    drop(in_flight);
+    metrics
+        .http_duration
+        .with_label_values(&[&method, &route])
+        .observe(started.elapsed().as_secs_f64());
+    metrics
+        .http_requests
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+    response
+}
+
+// GET /metrics，Prometheus 文本格式
+pub async fn render(State(state): State<AppState>) -> Response {
+    let metrics = &*METRICS;
+    metrics.update_pool(&state.db);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&metrics.registry.gather(), &mut buffer) {
+        tracing::error!(error = %e, "导出指标失败");
+    }
+    ([(header::CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_statement_operation_and_table() {
+        assert_eq!(
+            describe_statement("SELECT `users`.`id` FROM `users` WHERE `id` = ?"),
+            ("select", "users".to_string())
+        );
+        assert_eq!(describe_statement("insert into \"users\" (name) values ($1)"), ("insert", "users".to_string()));
+        assert_eq!(describe_statement("UPDATE `users` SET `name` = ?"), ("update", "users".to_string()));
+        assert_eq!(describe_statement("DELETE FROM refresh_tokens WHERE id = ?"), ("delete", "refresh_tokens".to_string()));
+        assert_eq!(
+            describe_statement("SELECT COUNT(*) FROM (SELECT 1 FROM users) AS t"),
+            ("select", "subquery".to_string())
+        );
+        assert_eq!(describe_statement("BEGIN"), ("other", String::new()));
+        assert_eq!(describe_statement(""), ("other", String::new()));
+    }
+}
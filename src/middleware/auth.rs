@@ -1,13 +1,38 @@
-use axum::{http::StatusCode, response::{IntoResponse, Response}, Json, body::Bytes, extract::Request, middleware::Next};
-use tracing::{debug, trace, info};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use tracing::debug;
+use jsonwebtoken::{decode, decode_header, encode, Header, Validation};
 use serde::{Deserialize, Serialize};
-use std::env;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::audit::{AuditEvent, RequestMeta};
+use crate::config::JwtConfig;
+use crate::crypto;
+use crate::database::revoked_token;
+use crate::error::AppError;
+use crate::metrics::METRICS;
+use crate::middleware::jwt_keys::JwtKeys;
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32, // 用户ID
     pub exp: usize,
+    pub iat: usize,
+    pub nbf: usize,
+    // 令牌唯一ID，用于吊销单个访问令牌
+    pub jti: String,
+    pub iss: String,
+    pub aud: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    // 会话ID，即签发时的 refresh token family
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,86 +40,302 @@ pub struct AuthError {
     pub message: String,
 }
 
-// JWT配置结构
-pub struct JwtConfig {
-    pub secret: String,
-    pub expiration: i64,
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
 }
 
-impl JwtConfig {
-    pub fn new() -> Self {
-        JwtConfig {
-            secret: env::var("JWT_SECRET").unwrap_or_else(|_| "default_secret".to_string()),
-            expiration: 3600, // 1小时
-        }
-    }
+impl std::error::Error for AuthError {}
+
+// 校验签名算法、iss/aud 以及 exp/nbf（含时钟偏差）
+fn validation(config: &JwtConfig, algorithm: jsonwebtoken::Algorithm) -> Validation {
+    let mut validation = Validation::new(algorithm);
+    validation.leeway = config.leeway_secs;
+    validation.validate_nbf = true;
+    validation.set_issuer(&[&config.issuer]);
+    validation.set_audience(&[&config.audience]);
+    validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud"]);
+    validation
 }
 
-// 生成token
-pub fn generate_token(user_id: i32) -> Result<String, AuthError> {
-    let config = JwtConfig::new();
-    let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::seconds(config.expiration))
-        .expect("invalid timestamp")
-        .timestamp() as usize;
+// 生成token，非对称签名时在头部写入 kid
+pub fn generate_token(
+    keys: &JwtKeys,
+    config: &JwtConfig,
+    user_id: i32,
+    roles: Vec<String>,
+    session_id: Option<String>,
+) -> Result<String, AuthError> {
+    let now = chrono::Utc::now().timestamp() as usize;
 
     let claims = Claims {
         sub: user_id,
-        exp: expiration,
+        exp: now + config.access_ttl_secs as usize,
+        iat: now,
+        nbf: now,
+        jti: crypto::random_id(),
+        iss: config.issuer.clone(),
+        aud: config.audience.clone(),
+        roles,
+        sid: session_id,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(config.secret.as_ref()),
-    )
+    let mut header = Header::new(keys.algorithm);
+    header.kid = keys.kid.clone();
+
+    encode(&header, &claims, keys.encoding_key())
     .map_err(|_| AuthError {
         message: "生成token失败".to_string(),
     })
 }
 
-// 验证中间件
-pub async fn auth_middleware(request: Request, next: Next) -> Result<Response, AuthError> {
-    let token = request
-        .headers()
-        .get("Authorization")
-        .map(|header| {
-            println!("发现Authorization请求头: {:?}", header);
-            header
-        })
-        .and_then(|header| header.to_str().ok())
-        // .and_then(|header| {
-        //     header.strip_prefix("Bearer ")
-        //         .or_else(|| header.strip_prefix("Bearer"))
-        //         .filter(|s| !s.is_empty())
-        //         .map(str::trim)
-        // })
-        .ok_or_else(|| {
-            println!("无效的Bearer格式或空令牌");
-            AuthError {
-                message: "无效的认证头".to_string(),
-            }
-        })?;
+// 按 RFC 6750 提取令牌：优先 Authorization: Bearer <token>（scheme 不区分大小写），
+// 其次回退到 access_token cookie
+pub fn extract_token(headers: &HeaderMap) -> Option<String> {
+    if let Some(value) = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) {
+        let (scheme, token) = value.trim().split_once(' ')?;
+        let token = token.trim();
+        if scheme.eq_ignore_ascii_case("bearer") && !token.is_empty() {
+            return Some(token.to_string());
+        }
+        return None;
+    }
 
-    let config = JwtConfig::new();
-    let validation = Validation::default();
-    
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(config.secret.as_ref()),
-        &validation,
-    )
-    .map_err(|_| AuthError {
-        message: "无效的token".to_string(),
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, value)| *name == "access_token" && !value.is_empty())
+        .map(|(_, value)| value.to_string())
+}
+
+fn failure_reason(e: &jsonwebtoken::errors::Error) -> &'static str {
+    use jsonwebtoken::errors::ErrorKind;
+    match e.kind() {
+        ErrorKind::ExpiredSignature => "expired",
+        ErrorKind::ImmatureSignature => "not_yet_valid",
+        ErrorKind::InvalidSignature => "invalid_signature",
+        ErrorKind::InvalidIssuer => "invalid_issuer",
+        ErrorKind::InvalidAudience => "invalid_audience",
+        ErrorKind::InvalidAlgorithm => "invalid_algorithm",
+        ErrorKind::MissingRequiredClaim(_) | ErrorKind::Json(_) => "invalid_claims",
+        _ => "malformed",
+    }
+}
+
+// 校验令牌，失败时返回用于指标统计的原因
+fn verify_token(keys: &JwtKeys, config: &JwtConfig, token: &str) -> Result<Claims, &'static str> {
+    let invalid = |e: jsonwebtoken::errors::Error| {
+        debug!(error = %e, "token校验失败");
+        failure_reason(&e)
+    };
+
+    // 按 kid 选择验签密钥，算法由密钥决定而不是令牌头
+    let header = decode_header(token).map_err(invalid)?;
+    let (algorithm, key) = keys.decoding_key(header.kid.as_deref()).ok_or_else(|| {
+        debug!(kid = ?header.kid, "未知的签名密钥");
+        "unknown_kid"
     })?;
 
+    let claims = decode::<Claims>(token, key, &validation(config, algorithm))
+        .map_err(invalid)?
+        .claims;
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    if claims.iat as u64 > now + config.leeway_secs {
+        debug!(iat = claims.iat, "token签发时间晚于当前时间");
+        return Err("issued_in_future");
+    }
+    Ok(claims)
+}
+
+// 校验签名、iss/aud/exp/nbf 以及 iat 是否来自未来，不检查吊销列表
+pub fn decode_claims(keys: &JwtKeys, config: &JwtConfig, token: &str) -> Result<Claims, AuthError> {
+    verify_token(keys, config, token).map_err(|_| AuthError {
+        message: "无效的token".to_string(),
+    })
+}
+
+// 验证中间件，校验通过后将 Claims 放入请求扩展；失败时返回 ApiResponse 格式的 401
+pub async fn auth_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(token) = extract_token(request.headers()) else {
+        debug!("无效的Bearer格式或空令牌");
+        return Err(reject(&state, request_meta(&state, &request), "missing", None, "无效的认证头"));
+    };
+
+    let claims = match verify_token(&state.jwt, &state.config.jwt, &token) {
+        Ok(claims) => claims,
+        Err(reason) => return Err(reject(&state, request_meta(&state, &request), reason, None, "无效的token")),
+    };
+
+    match revoked_token::is_revoked(&state.db, &claims.jti, claims.sid.as_deref()).await {
+        Ok(false) => {}
+        Ok(true) => {
+            debug!(jti = %claims.jti, "token已被吊销");
+            return Err(reject(&state, request_meta(&state, &request), "revoked", Some(claims.sub), "token已被吊销"));
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    tracing::Span::current().record("user_id", claims.sub);
+    request.extensions_mut().insert(claims);
     Ok(next.run(request).await)
 }
 
-impl IntoResponse for AuthError {
-    fn into_response(self) -> Response {
-        let status = StatusCode::UNAUTHORIZED;
-        let body = Json(self);
-        (status, body).into_response()
+fn request_meta(state: &AppState, request: &Request) -> RequestMeta {
+    RequestMeta::from_parts(request.headers(), request.extensions(), state.config.rate_limit.trusted_hops())
+}
+
+// 认证失败时记录指标和审计事件；匿名请求即可触发，审计事件在队列繁忙时丢弃
+fn reject(
+    state: &AppState,
+    meta: RequestMeta,
+    reason: &'static str,
+    actor_id: Option<i32>,
+    message: &str,
+) -> AppError {
+    METRICS.token_failure(reason);
+
+    let mut event = AuditEvent::new("auth.rejected", &meta).failure(reason);
+    if let Some(actor_id) = actor_id {
+        event = event.actor(actor_id);
+    }
+    state.audit.try_record(event);
+
+    AuthError {
+        message: message.to_string(),
+    }
+    .into()
+}
+
+// 已认证的调用者，需在 auth_middleware 之后使用
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: i32,
+    pub roles: Vec<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts.extensions.get::<Claims>().ok_or_else(|| AuthError {
+            message: "未认证".to_string(),
+        })?;
+
+        Ok(AuthUser {
+            id: claims.sub,
+            roles: claims.roles.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn keys() -> (JwtKeys, JwtConfig) {
+        let config = JwtConfig {
+            secret: "unit-test-secret-0123456789abcdef".to_string(),
+            ..Default::default()
+        };
+        (JwtKeys::from_config(&config).unwrap(), config)
+    }
+
+    // 按给定 claims 直接签名，用于构造各种不合规的令牌
+    fn sign(keys: &JwtKeys, claims: &Value) -> String {
+        encode(&Header::new(keys.algorithm), claims, keys.encoding_key()).unwrap()
     }
-}
\ No newline at end of file
+
+    fn valid_claims(config: &JwtConfig) -> Value {
+        let now = chrono::Utc::now().timestamp();
+        json!({
+            "sub": 7,
+            "exp": now + 60,
+            "iat": now,
+            "nbf": now,
+            "jti": "jti-1",
+            "iss": config.issuer,
+            "aud": config.audience,
+            "sid": "session-1",
+        })
+    }
+
+    #[test]
+    fn verify_token_accepts_valid_claims() {
+        let (keys, config) = keys();
+        let claims = verify_token(&keys, &config, &sign(&keys, &valid_claims(&config))).unwrap();
+        assert_eq!((claims.sub, claims.jti.as_str(), claims.sid.as_deref()), (7, "jti-1", Some("session-1")));
+    }
+
+    #[test]
+    fn verify_token_rejects_bad_claims() {
+        let (keys, config) = keys();
+        let now = chrono::Utc::now().timestamp();
+        let cases: [(&str, Value, &str); 5] = [
+            ("iss", json!("someone-else"), "invalid_issuer"),
+            ("aud", json!("other-api"), "invalid_audience"),
+            ("nbf", json!(now + 3600), "not_yet_valid"),
+            ("iat", json!(now + 3600), "issued_in_future"),
+            ("jti", Value::Null, "invalid_claims"),
+        ];
+        for (field, value, reason) in cases {
+            let mut claims = valid_claims(&config);
+            match value {
+                Value::Null => claims.as_object_mut().unwrap().remove(field),
+                value => claims.as_object_mut().unwrap().insert(field.to_string(), value),
+            };
+            assert_eq!(verify_token(&keys, &config, &sign(&keys, &claims)).err(), Some(reason), "{}", field);
+        }
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name.clone(), value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn extract_token_reads_bearer_case_insensitively() {
+        let h = headers(&[(header::AUTHORIZATION, "bearer  abc.def ")]);
+        assert_eq!(extract_token(&h).as_deref(), Some("abc.def"));
+        let h = headers(&[(header::AUTHORIZATION, "Bearer abc")]);
+        assert_eq!(extract_token(&h).as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn extract_token_rejects_other_schemes_without_cookie_fallback() {
+        let h = headers(&[
+            (header::AUTHORIZATION, "Basic dXNlcjpwYXNz"),
+            (header::COOKIE, "access_token=abc"),
+        ]);
+        assert_eq!(extract_token(&h), None);
+        let h = headers(&[(header::AUTHORIZATION, "Bearer ")]);
+        assert_eq!(extract_token(&h), None);
+    }
+
+    #[test]
+    fn extract_token_falls_back_to_cookie() {
+        let h = headers(&[
+            (header::COOKIE, "theme=dark; access_token="),
+            (header::COOKIE, "lang=zh; access_token=abc"),
+        ]);
+        assert_eq!(extract_token(&h).as_deref(), Some("abc"));
+        assert_eq!(extract_token(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sea_orm::DbErr;
+use sha2::{Digest, Sha256};
+
+use crate::crypto;
+use crate::database::IdempotencyStore;
+use crate::error::AppError;
+use crate::middleware::auth::Claims;
+use crate::middleware::request_id::current_request_id;
+use crate::state::AppState;
+
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+// 各部分之间以 0 字节分隔，避免拼接后产生歧义
+fn hash_parts(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+        hasher.update([0u8]);
+    }
+    crypto::hex(&hasher.finalize())
+}
+
+// 请求未走到保存或释放就被取消（客户端断开、停机超时、panic）时，后台释放占位
+struct PendingGuard {
+    store: Arc<dyn IdempotencyStore>,
+    key_hash: String,
+    armed: bool,
+}
+
+impl PendingGuard {
+    async fn release(mut self) -> Result<(), DbErr> {
+        self.armed = false;
+        self.store.release(&self.key_hash).await
+    }
+
+    async fn complete(mut self, status_code: u16, content_type: Option<String>, body: String) -> Result<(), DbErr> {
+        self.armed = false;
+        self.store.complete(&self.key_hash, status_code, content_type, body).await
+    }
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let store = self.store.clone();
+        let key_hash = self.key_hash.clone();
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                if let Err(e) = store.release(&key_hash).await {
+                    tracing::warn!(error = %e, "释放幂等占位失败");
+                }
+            });
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+fn is_no_store(response: &Response) -> bool {
+    response
+        .headers()
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.split(',').any(|d| d.trim().eq_ignore_ascii_case("no-store")))
+}
+
+// 重放的响应体中 request_id 属于首个请求，改写为本次请求的 ID，与 X-Request-Id 一致
+fn with_current_request_id(body: String) -> String {
+    let Ok(serde_json::Value::Object(mut map)) = serde_json::from_str(&body) else {
+        return body;
+    };
+    if !map.contains_key("request_id") {
+        return body;
+    }
+    map.insert("request_id".to_string(), serde_json::json!(current_request_id()));
+    serde_json::Value::Object(map).to_string()
+}
+
+// POST 请求携带 Idempotency-Key 时，首个响应按 (用户, 方法, 路径, key) 保存并在重复请求时原样返回；
+// 放在 auth_middleware 之后可按用户隔离，公开路由统一视为匿名用户
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if request.method() != Method::POST {
+        return Ok(next.run(request).await);
+    }
+    let Some(key) = request
+        .headers()
+        .get(IDEMPOTENCY_KEY)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+    else {
+        return Ok(next.run(request).await);
+    };
+    if key.is_empty() || key.len() > 255 {
+        return Err(AppError::Validation("Idempotency-Key长度需为1-255个字符".to_string()));
+    }
+
+    let user = request
+        .extensions()
+        .get::<Claims>()
+        .map(|c| c.sub.to_string())
+        .unwrap_or_else(|| "anonymous".to_string());
+    let path = request.uri().path().to_string();
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::Validation("请求体过大".to_string()))?;
+
+    let key_hash = hash_parts(&[user.as_bytes(), parts.method.as_str().as_bytes(), path.as_bytes(), key.as_bytes()]);
+    let request_hash = hash_parts(&[parts.method.as_str().as_bytes(), path.as_bytes(), &body]);
+    let store = state.idempotency.clone();
+    let ttl = chrono::Duration::seconds(state.config.idempotency.ttl_secs);
+    let lease = chrono::Duration::seconds(state.config.idempotency.lock_timeout_secs);
+
+    if !store.try_begin(&key_hash, &request_hash, ttl, lease).await? {
+        let record = store
+            .find(&key_hash)
+            .await?
+            .ok_or_else(|| AppError::Internal("幂等记录不存在".to_string()))?;
+        if record.request_hash != request_hash {
+            return Err(AppError::Validation("Idempotency-Key已用于不同的请求".to_string()));
+        }
+        let (Some(status), Some(body)) = (record.status_code, record.response_body) else {
+            return Err(AppError::Conflict {
+                field: "Idempotency-Key".to_string(),
+                message: "相同Idempotency-Key的请求正在处理".to_string(),
+            });
+        };
+
+        let status = StatusCode::from_u16(status as u16).unwrap_or(StatusCode::OK);
+        let mut response = (status, with_current_request_id(body)).into_response();
+        if let Some(content_type) = record.content_type.and_then(|v| HeaderValue::from_str(&v).ok()) {
+            response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+        }
+        response
+            .headers_mut()
+            .insert("idempotent-replayed", HeaderValue::from_static("true"));
+        return Ok(response);
+    }
+
+    let guard = PendingGuard {
+        store,
+        key_hash,
+        armed: true,
+    };
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // 服务端错误和限流、超时等临时性失败不缓存，允许客户端用同一个 key 重试；
+    // 标记 no-store 的响应含有凭据，同样不保存
+    if is_transient(response.status()) || is_no_store(&response) {
+        guard.release().await?;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            guard.release().await?;
+            return Err(AppError::Internal(format!("读取响应失败: {}", e)));
+        }
+    };
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    guard
+        .complete(parts.status.as_u16(), content_type, String::from_utf8_lossy(&body).into_owned())
+        .await?;
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{
+        extract::State,
+        http::{HeaderMap, Request},
+        middleware::{from_fn, from_fn_with_state},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::middleware::request_id::{request_id_middleware, X_REQUEST_ID};
+
+    const BODY: &str = r#"{"a":1}"#;
+
+    // 按 x-status 返回指定状态码，并返回处理器被调用的次数
+    async fn handler(State(calls): State<Arc<AtomicUsize>>, headers: HeaderMap) -> Response {
+        let calls = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let status = headers
+            .get("x-status")
+            .and_then(|v| v.to_str().ok()?.parse().ok())
+            .and_then(|v| StatusCode::from_u16(v).ok())
+            .unwrap_or(StatusCode::CREATED);
+        (status, Json(json!({ "calls": calls, "request_id": current_request_id() }))).into_response()
+    }
+
+    async fn setup() -> (Router, AppState, Arc<AtomicUsize>) {
+        let (state, _, _) = crate::api::tests::test_state().await;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/echo", post(handler))
+            .with_state(calls.clone())
+            .layer(from_fn_with_state(state.clone(), idempotency_middleware))
+            .layer(from_fn(request_id_middleware));
+        (app, state, calls)
+    }
+
+    async fn send(app: &Router, body: &str, status: u16, request_id: &str) -> (StatusCode, HeaderMap, Value) {
+        let request = Request::post("/echo")
+            .header(IDEMPOTENCY_KEY, "k1")
+            .header("x-status", status.to_string())
+            .header(&X_REQUEST_ID, request_id)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = to_bytes(body, usize::MAX).await.unwrap();
+        (parts.status, parts.headers, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    fn hashes(body: &str) -> (String, String) {
+        (
+            hash_parts(&[b"anonymous", b"POST", b"/echo", b"k1"]),
+            hash_parts(&[b"POST", b"/echo", body.as_bytes()]),
+        )
+    }
+
+    #[tokio::test]
+    async fn replays_stored_response_with_current_request_id() {
+        let (app, _, calls) = setup().await;
+        let (status, headers, first) = send(&app, BODY, 201, "first").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(headers.get("idempotent-replayed").is_none());
+        assert_eq!(first["request_id"], "first");
+
+        let (status, headers, replayed) = send(&app, BODY, 201, "second").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(headers["idempotent-replayed"], "true");
+        assert_eq!(headers[&X_REQUEST_ID], "second");
+        assert_eq!(replayed, json!({ "calls": 1, "request_id": "second" }));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_same_key_with_different_body() {
+        let (app, _, calls) = setup().await;
+        send(&app, BODY, 201, "first").await;
+        let (status, _, _) = send(&app, r#"{"a":2}"#, 201, "second").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn conflicts_while_first_request_is_in_flight() {
+        let (app, state, calls) = setup().await;
+        let (key_hash, request_hash) = hashes(BODY);
+        let started = state
+            .idempotency
+            .try_begin(&key_hash, &request_hash, chrono::Duration::hours(1), chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert!(started);
+
+        let (status, _, _) = send(&app, BODY, 201, "second").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn takes_over_an_expired_lease() {
+        let (app, state, calls) = setup().await;
+        let (key_hash, request_hash) = hashes(BODY);
+        state
+            .idempotency
+            .try_begin(&key_hash, &request_hash, chrono::Duration::hours(1), chrono::Duration::seconds(-1))
+            .await
+            .unwrap();
+
+        let (status, headers, body) = send(&app, BODY, 201, "retry").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(headers.get("idempotent-replayed").is_none());
+        assert_eq!(body["calls"], 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn releases_key_after_transient_failure() {
+        let (app, _, calls) = setup().await;
+        for transient in [503, 429] {
+            let (status, _, _) = send(&app, BODY, transient, "failed").await;
+            assert_eq!(status.as_u16(), transient);
+        }
+
+        let (status, headers, body) = send(&app, BODY, 201, "retry").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(headers.get("idempotent-replayed").is_none());
+        assert_eq!(body["calls"], 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,2 +1,9 @@
 pub mod auth;
-pub use auth::auth_middleware;
\ No newline at end of file
+pub mod idempotency;
+pub mod jwt_keys;
+pub mod rate_limit;
+pub mod rbac;
+pub mod request_id;
+pub use auth::{auth_middleware, AuthUser};
+pub use idempotency::idempotency_middleware;
+pub use rbac::require_permission;
@@ -0,0 +1,506 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::{Extensions, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::config::{LoginConfig, RateLimitConfig};
+use crate::crypto;
+use crate::database::mysql_orm;
+use crate::error::AppError;
+use crate::metrics::METRICS;
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+
+// 令牌桶参数：容量 capacity，每 period 补满一次
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    pub const fn per_minute(capacity: u32) -> Self {
+        RateLimit {
+            capacity,
+            period: Duration::from_secs(60),
+        }
+    }
+
+    // 格式: "<次数>/<秒数>"，例如 "5/60"
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (capacity, secs) = raw.split_once('/')?;
+        let capacity: u32 = capacity.trim().parse().ok()?;
+        let secs: u64 = secs.trim().parse().ok()?;
+        (capacity > 0 && secs > 0).then(|| RateLimit {
+            capacity,
+            period: Duration::from_secs(secs),
+        })
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.capacity as f64 / self.period.as_secs_f64()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    // 桶补满所需时间
+    pub reset_after: Duration,
+    // 被拒绝时距离下一个令牌的时间
+    pub retry_after: Duration,
+}
+
+// 限流与登录失败计数的存储，默认内存实现，多实例部署时可替换为共享存储
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn check(&self, key: &str, limit: RateLimit) -> Decision;
+
+    // 账号仍处于锁定状态时返回剩余锁定时间
+    async fn lockout_remaining(&self, account: &str) -> Option<Duration>;
+
+    // 记录一次密码校验失败，触发锁定时返回锁定时长
+    async fn record_failure(&self, account: &str, policy: &LockoutPolicy) -> Option<Duration>;
+
+    async fn reset_failures(&self, account: &str);
+
+    // 清理已经补满的桶和过期的失败计数，返回清理的条数
+    async fn purge_expired(&self) -> u64;
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    // 每个桶按自己的周期判断是否已补满
+    period: Duration,
+}
+
+impl Bucket {
+    fn is_full(&self, now: Instant) -> bool {
+        now.duration_since(self.updated) >= self.period
+    }
+}
+
+struct FailureState {
+    failures: u32,
+    locked_until: Option<Instant>,
+    updated: Instant,
+    window: Duration,
+}
+
+impl FailureState {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.locked_until.is_none_or(|until| until <= now) && now.duration_since(self.updated) > self.window
+    }
+}
+
+// 按最近访问顺序淘汰的有界表，条目数达到 capacity 时淘汰最久未访问的条目
+struct LruMap<V> {
+    capacity: usize,
+    entries: HashMap<String, (u64, V)>,
+    order: BTreeMap<u64, String>,
+    next_seq: u64,
+}
+
+impl<V> LruMap<V> {
+    fn new(capacity: usize) -> Self {
+        LruMap {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&V> {
+        self.entries.get(key).map(|(_, value)| value)
+    }
+
+    // 取出条目并标记为最近访问，不存在时用 default 插入
+    fn touch(&mut self, key: &str, default: impl FnOnce() -> V) -> &mut V {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                self.order.remove(&entry.0);
+                entry.0 = seq;
+            }
+            None => {
+                if self.entries.len() >= self.capacity {
+                    if let Some((_, oldest)) = self.order.pop_first() {
+                        self.entries.remove(&oldest);
+                    }
+                }
+                self.entries.insert(key.to_string(), (seq, default()));
+            }
+        }
+        self.order.insert(seq, key.to_string());
+        &mut self.entries.get_mut(key).expect("刚插入的条目").1
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((seq, _)) = self.entries.remove(key) {
+            self.order.remove(&seq);
+        }
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&V) -> bool) -> usize {
+        let before = self.entries.len();
+        let order = &mut self.order;
+        self.entries.retain(|_, (seq, value)| {
+            let kept = keep(value);
+            if !kept {
+                order.remove(seq);
+            }
+            kept
+        });
+        before - self.entries.len()
+    }
+}
+
+// 内存中最多跟踪的 key 数，超过后淘汰最久未访问的；过期条目由后台任务定期清理
+const MAX_TRACKED_KEYS: usize = 100_000;
+
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct InMemoryRateLimitStore {
+    buckets: Mutex<LruMap<Bucket>>,
+    failures: Mutex<LruMap<FailureState>>,
+}
+
+impl Default for InMemoryRateLimitStore {
+    fn default() -> Self {
+        InMemoryRateLimitStore {
+            buckets: Mutex::new(LruMap::new(MAX_TRACKED_KEYS)),
+            failures: Mutex::new(LruMap::new(MAX_TRACKED_KEYS)),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn check(&self, key: &str, limit: RateLimit) -> Decision {
+        let now = Instant::now();
+        let rate = limit.refill_per_sec();
+        let capacity = limit.capacity as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let bucket = buckets.touch(key, || Bucket {
+            tokens: capacity,
+            updated: now,
+            period: limit.period,
+        });
+        bucket.period = limit.period;
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        Decision {
+            allowed,
+            limit: limit.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            reset_after: Duration::from_secs_f64((capacity - bucket.tokens) / rate),
+            retry_after: Duration::from_secs_f64(((1.0 - bucket.tokens) / rate).max(0.0)),
+        }
+    }
+
+    async fn lockout_remaining(&self, account: &str) -> Option<Duration> {
+        let now = Instant::now();
+        let failures = self.failures.lock().unwrap();
+        failures
+            .get(account)
+            .and_then(|state| state.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    async fn record_failure(&self, account: &str, policy: &LockoutPolicy) -> Option<Duration> {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+
+        let state = failures.touch(account, || FailureState {
+            failures: 0,
+            locked_until: None,
+            updated: now,
+            window: policy.failure_window,
+        });
+        state.window = policy.failure_window;
+        // 超过统计窗口没有失败则重新计数
+        if now.duration_since(state.updated) > policy.failure_window {
+            state.failures = 0;
+        }
+        state.failures += 1;
+        state.updated = now;
+
+        let lockout = policy.lockout_for(state.failures)?;
+        state.locked_until = Some(now + lockout);
+        Some(lockout)
+    }
+
+    async fn reset_failures(&self, account: &str) {
+        self.failures.lock().unwrap().remove(account);
+    }
+
+    async fn purge_expired(&self) -> u64 {
+        let now = Instant::now();
+        let buckets = self.buckets.lock().unwrap().retain(|b| !b.is_full(now));
+        let failures = self.failures.lock().unwrap().retain(|s| !s.is_expired(now));
+        (buckets + failures) as u64
+    }
+}
+
+// 渐进式锁定：连续失败达到 max_failures 次后锁定 base，之后每多失败一次锁定时长翻倍
+#[derive(Debug, Clone)]
+pub struct LockoutPolicy {
+    pub max_failures: u32,
+    pub base: Duration,
+    pub max: Duration,
+    pub failure_window: Duration,
+}
+
+impl LockoutPolicy {
+    pub fn from_config(config: &LoginConfig) -> Self {
+        LockoutPolicy {
+            max_failures: config.max_failures,
+            base: Duration::from_secs(config.lockout_secs),
+            max: Duration::from_secs(config.lockout_max_secs),
+            failure_window: Duration::from_secs(config.failure_window_secs),
+        }
+    }
+
+    fn lockout_for(&self, failures: u32) -> Option<Duration> {
+        if failures < self.max_failures {
+            return None;
+        }
+        let exponent = (failures - self.max_failures).min(16);
+        Some(self.base.saturating_mul(1 << exponent).min(self.max))
+    }
+}
+
+// 路由级限流策略，三个维度分别独立计数，任一维度超限即拒绝
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitPolicy {
+    pub name: &'static str,
+    pub ip: Option<RateLimit>,
+    // 按请求体中的 email 字段
+    pub account: Option<RateLimit>,
+    // 按 JWT sub，需放在 auth_middleware 之后
+    pub subject: Option<RateLimit>,
+}
+
+impl RateLimitPolicy {
+    // 用 rate_limit.policies.<name> 覆盖默认值，
+    // 对应环境变量 RATE_LIMIT_<NAME>_IP / _ACCOUNT / _SUBJECT，例如 RATE_LIMIT_LOGIN_IP=20/60
+    pub fn with_overrides(self, config: &RateLimitConfig) -> Self {
+        let Some(overrides) = config.policies.get(self.name) else {
+            return self;
+        };
+        let read = |value: &Option<String>, default: Option<RateLimit>| match value.as_deref() {
+            Some("off") => None,
+            Some(v) => RateLimit::parse(v).or(default),
+            None => default,
+        };
+        RateLimitPolicy {
+            ip: read(&overrides.ip, self.ip),
+            account: read(&overrides.account, self.account),
+            subject: read(&overrides.subject, self.subject),
+            ..self
+        }
+    }
+}
+
+// 每层代理都把它看到的对端地址追加到 X-Forwarded-For 末尾，左侧的条目可由客户端伪造，
+// 所以从右往左数过 trusted_hops 层可信代理取客户端地址；为 0 时只使用连接的对端地址
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions, trusted_hops: usize) -> Option<String> {
+    if trusted_hops > 0 {
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .collect();
+        let forwarded = forwarded
+            .len()
+            .checked_sub(trusted_hops)
+            .and_then(|i| forwarded[i].parse::<IpAddr>().ok());
+        if let Some(ip) = forwarded {
+            return Some(ip.to_string());
+        }
+    }
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+}
+
+fn rate_limit_headers(headers: &mut HeaderMap, decision: &Decision) {
+    let mut set = |name: &'static str, value: u64| {
+        headers.insert(name, HeaderValue::from(value));
+    };
+    set("ratelimit-limit", decision.limit as u64);
+    set("ratelimit-remaining", decision.remaining as u64);
+    set("ratelimit-reset", decision.reset_after.as_secs_f64().ceil() as u64);
+}
+
+pub fn too_many_requests(retry_after: Duration, message: &str) -> AppError {
+    AppError::TooManyRequests {
+        message: message.to_string(),
+        retry_after: retry_after.as_secs_f64().ceil().max(1.0) as u64,
+    }
+}
+
+type LimitFuture = Pin<Box<dyn Future<Output = Result<Response, AppError>> + Send>>;
+
+// 路由限流中间件：.route_layer(from_fn_with_state(state, rate_limit(policy)))
+pub fn rate_limit(
+    policy: RateLimitPolicy,
+) -> impl Fn(State<AppState>, Request, Next) -> LimitFuture + Clone + Send + Sync + 'static {
+    move |State(state): State<AppState>, request: Request, next: Next| {
+        let policy = policy.clone();
+        Box::pin(async move {
+            let mut keys = Vec::new();
+            if let Some(limit) = policy.ip {
+                if let Some(ip) = client_ip(request.headers(), request.extensions(), state.config.rate_limit.trusted_hops()) {
+                    keys.push((format!("{}:ip:{}", policy.name, ip), limit));
+                }
+            }
+            if let Some(limit) = policy.subject {
+                if let Some(claims) = request.extensions().get::<Claims>() {
+                    keys.push((format!("{}:sub:{}", policy.name, claims.sub), limit));
+                }
+            }
+
+            let request = if let Some(limit) = policy.account {
+                let (parts, body) = request.into_parts();
+                let body = to_bytes(body, 1024 * 1024)
+                    .await
+                    .map_err(|_| AppError::Validation("请求体过大".to_string()))?;
+                // 与账号查询使用同样的规范化，取哈希使 key 长度固定
+                let email = serde_json::from_slice::<serde_json::Value>(&body)
+                    .ok()
+                    .and_then(|v| v.get("email").and_then(|e| e.as_str()).map(mysql_orm::normalize_email));
+                if let Some(email) = email {
+                    let account = crypto::sha256_hex(email.as_bytes());
+                    keys.push((format!("{}:account:{}", policy.name, account), limit));
+                }
+                Request::from_parts(parts, Body::from(body))
+            } else {
+                request
+            };
+
+            // 取剩余额度最少的维度作为响应头
+            let mut tightest: Option<Decision> = None;
+            for (key, limit) in keys {
+                let decision = state.rate_limiter.check(&key, limit).await;
+                if !decision.allowed {
+                    tracing::info!(key = %key, "触发限流");
+                    METRICS.rate_limited.with_label_values(&[policy.name]).inc();
+                    let mut response = too_many_requests(decision.retry_after, "请求过于频繁，请稍后再试").into_response();
+                    rate_limit_headers(response.headers_mut(), &decision);
+                    return Ok(response);
+                }
+                if tightest.is_none_or(|t| decision.remaining < t.remaining) {
+                    tightest = Some(decision);
+                }
+            }
+
+            let mut response = next.run(request).await;
+            if let Some(decision) = tightest {
+                rate_limit_headers(response.headers_mut(), &decision);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rate_limit() {
+        let limit = RateLimit::parse(" 5 / 60 ").unwrap();
+        assert_eq!(limit.capacity, 5);
+        assert_eq!(limit.period, Duration::from_secs(60));
+        for raw in ["0/60", "5/0", "5", "off", "-1/60", "a/b", ""] {
+            assert!(RateLimit::parse(raw).is_none(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn lockout_doubles_up_to_max() {
+        let policy = LockoutPolicy {
+            max_failures: 3,
+            base: Duration::from_secs(60),
+            max: Duration::from_secs(300),
+            failure_window: Duration::from_secs(900),
+        };
+        assert_eq!(policy.lockout_for(2), None);
+        assert_eq!(policy.lockout_for(3), Some(Duration::from_secs(60)));
+        assert_eq!(policy.lockout_for(4), Some(Duration::from_secs(120)));
+        assert_eq!(policy.lockout_for(5), Some(Duration::from_secs(240)));
+        assert_eq!(policy.lockout_for(6), Some(Duration::from_secs(300)));
+        assert_eq!(policy.lockout_for(u32::MAX), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn lru_map_evicts_least_recently_used() {
+        let mut map = LruMap::new(2);
+        map.touch("a", || 1);
+        map.touch("b", || 2);
+        map.touch("a", || 0);
+        map.touch("c", || 3);
+        assert!(map.get("b").is_none());
+        assert_eq!((map.get("a"), map.get("c")), (Some(&1), Some(&3)));
+        assert_eq!(map.entries.len(), map.order.len());
+    }
+
+    #[tokio::test]
+    async fn purge_uses_each_bucket_period() {
+        let store = InMemoryRateLimitStore::default();
+        let short = RateLimit {
+            capacity: 1,
+            period: Duration::from_millis(1),
+        };
+        store.check("short", short).await;
+        store.check("long", RateLimit::per_minute(1)).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        assert_eq!(store.purge_expired().await, 1);
+        assert!(!store.check("long", RateLimit::per_minute(1)).await.allowed);
+    }
+
+    #[test]
+    fn client_ip_counts_trusted_hops_from_the_right() {
+        let mut headers = HeaderMap::new();
+        headers.append("x-forwarded-for", "6.6.6.6, 1.1.1.1".parse().unwrap());
+        headers.append("x-forwarded-for", "10.0.0.1".parse().unwrap());
+        let mut extensions = Extensions::new();
+        extensions.insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 2], 443))));
+
+        assert_eq!(client_ip(&headers, &extensions, 0).as_deref(), Some("10.0.0.2"));
+        assert_eq!(client_ip(&headers, &extensions, 1).as_deref(), Some("10.0.0.1"));
+        assert_eq!(client_ip(&headers, &extensions, 2).as_deref(), Some("1.1.1.1"));
+        // 条目不足或不是合法地址时回退到对端地址
+        assert_eq!(client_ip(&headers, &extensions, 4).as_deref(), Some("10.0.0.2"));
+        headers.insert("x-forwarded-for", "not-an-ip".parse().unwrap());
+        assert_eq!(client_ip(&headers, &extensions, 1).as_deref(), Some("10.0.0.2"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use sea_orm::DatabaseConnection;
+
+use crate::database::role;
+use crate::error::{AppError, AppResult};
+use crate::middleware::auth::{AuthUser, Claims};
+use crate::state::AppState;
+
+// 角色展开后的权限集合，支持 "*" 和 "users:*" 通配
+#[derive(Debug, Clone, Default)]
+pub struct Permissions(HashSet<String>);
+
+impl Permissions {
+    pub fn allows(&self, permission: &str) -> bool {
+        if self.0.contains("*") || self.0.contains(permission) {
+            return true;
+        }
+        permission
+            .split_once(':')
+            .map(|(resource, _)| self.0.contains(&format!("{}:*", resource)))
+            .unwrap_or(false)
+    }
+}
+
+pub async fn resolve_permissions(db: &DatabaseConnection, roles: &[String]) -> AppResult<Permissions> {
+    let mut permissions = HashSet::new();
+    let mut custom = Vec::new();
+    for name in roles {
+        match role::builtin_permissions(name) {
+            Some(builtin) => permissions.extend(builtin.iter().map(|p| p.to_string())),
+            None => custom.push(name.clone()),
+        }
+    }
+
+    for record in role::find_roles(db, &custom).await? {
+        permissions.extend(record.permission_list().map(str::to_string));
+    }
+
+    Ok(Permissions(permissions))
+}
+
+type GuardFuture = Pin<Box<dyn Future<Output = Result<Response, AppError>> + Send>>;
+
+// 路由权限守卫，需放在 auth_middleware 之后：
+// .route_layer(from_fn_with_state(state, require_permission("users:delete")))
+pub fn require_permission(
+    permission: &'static str,
+) -> impl Fn(State<AppState>, Request, Next) -> GuardFuture + Clone + Send + Sync + 'static {
+    move |State(state): State<AppState>, request: Request, next: Next| {
+        Box::pin(async move {
+            let claims = request
+                .extensions()
+                .get::<Claims>()
+                .ok_or_else(|| AppError::Unauthorized("未认证".to_string()))?;
+
+            let permissions = resolve_permissions(&state.db, &claims.roles).await?;
+            if !permissions.allows(permission) {
+                tracing::info!(user_id = claims.sub, permission, "权限不足");
+                return Err(AppError::Forbidden(format!("缺少权限: {}", permission)));
+            }
+
+            Ok(next.run(request).await)
+        })
+    }
+}
+
+// 所有权规则：本人可以操作自己的记录，操作他人记录需要 override_permission
+pub async fn ensure_owner_or(
+    db: &DatabaseConnection,
+    auth_user: &AuthUser,
+    target_id: i32,
+    override_permission: &str,
+) -> AppResult<()> {
+    if auth_user.id == target_id {
+        return Ok(());
+    }
+    let permissions = resolve_permissions(db, &auth_user.roles).await?;
+    if permissions.allows(override_permission) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("只能修改自己的用户信息".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions(list: &[&str]) -> Permissions {
+        Permissions(list.iter().map(|p| p.to_string()).collect())
+    }
+
+    #[test]
+    fn allows_exact_and_wildcards() {
+        let p = permissions(&["users:read", "audit:*"]);
+        assert!(p.allows("users:read"));
+        assert!(!p.allows("users:write"));
+        assert!(p.allows("audit:read"));
+        assert!(!p.allows("audit"));
+        assert!(permissions(&["*"]).allows("anything:at_all"));
+        assert!(!Permissions::default().allows("users:read"));
+    }
+}
@@ -0,0 +1,81 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{field, Instrument};
+
+use crate::crypto::random_id;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// 当前请求的ID，供 ApiResponse 回显；不在请求上下文中时为空
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+// 只接受长度和字符集合理的外部ID，避免日志注入
+fn accept(value: &HeaderValue) -> Option<String> {
+    let value = value.to_str().ok()?;
+    let valid = !value.is_empty()
+        && value.len() <= 128
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    valid.then(|| value.to_string())
+}
+
+// 生成或沿用 X-Request-Id，整个请求在带有该ID的 span 中处理，并在响应头中回显。
+// 放在合并后的路由最外层，user_id 由 auth_middleware 记录到同一个 span 上
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(accept)
+        .unwrap_or_else(random_id);
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        request.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        route = %route,
+        status = field::Empty,
+        latency_ms = field::Empty,
+        user_id = field::Empty,
+    );
+    crate::telemetry::set_parent_from_headers(&span, request.headers());
+
+    let started = Instant::now();
+    let mut response = REQUEST_ID
+        .scope(request_id.clone(), next.run(request).instrument(span.clone()))
+        .await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    span.record("status", response.status().as_u16());
+    span.record("latency_ms", latency_ms);
+    span.in_scope(|| {
+        if response.status().is_server_error() {
+            tracing::warn!("请求完成");
+        } else {
+            tracing::info!("请求完成");
+        }
+    });
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+    response
+}
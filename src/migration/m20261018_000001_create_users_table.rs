@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_index_if_missing;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Users::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Users::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Users::Name).string_len(255).not_null())
+                    .col(ColumnDef::new(Users::Email).string_len(255).not_null())
+                    .col(ColumnDef::new(Users::Password).string_len(255).not_null())
+                    .col(ColumnDef::new(Users::Role).string_len(64).not_null().default("user"))
+                    .col(ColumnDef::new(Users::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Users::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有部署中的 users 表早于本迁移创建，if_not_exists 会跳过整个表定义，缺少的列单独补上
+        if !manager.has_column("users", "role").await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Users::Table)
+                        .add_column(ColumnDef::new(Users::Role).string_len(64).not_null().default("user"))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // 邮箱唯一索引在 m000004 规范化已有邮箱之后创建
+        create_index_if_missing(
+            manager,
+            Users::Table,
+            "idx_users_created_at",
+            Index::create().col(Users::CreatedAt).to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Users::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Users {
+    Table,
+    Id,
+    Name,
+    Email,
+    Password,
+    Role,
+    CreatedAt,
+    UpdatedAt,
+}
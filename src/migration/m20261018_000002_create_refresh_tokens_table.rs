@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_index_if_missing;
+use super::m20261018_000001_create_users_table::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefreshTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RefreshTokens::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RefreshTokens::UserId).integer().not_null())
+                    .col(ColumnDef::new(RefreshTokens::FamilyId).string_len(64).not_null())
+                    .col(ColumnDef::new(RefreshTokens::TokenHash).string_len(64).not_null())
+                    .col(ColumnDef::new(RefreshTokens::ExpiresAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(RefreshTokens::RevokedAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(RefreshTokens::CreatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_refresh_tokens_user_id")
+                            .from(RefreshTokens::Table, RefreshTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        create_index_if_missing(
+            manager,
+            RefreshTokens::Table,
+            "uk_refresh_tokens_token_hash",
+            Index::create().col(RefreshTokens::TokenHash).unique().to_owned(),
+        )
+        .await?;
+
+        create_index_if_missing(
+            manager,
+            RefreshTokens::Table,
+            "idx_refresh_tokens_family_id",
+            Index::create().col(RefreshTokens::FamilyId).to_owned(),
+        )
+        .await?;
+
+        // 定时清理按 expires_at 删除过期的刷新令牌
+        create_index_if_missing(
+            manager,
+            RefreshTokens::Table,
+            "idx_refresh_tokens_expires_at",
+            Index::create().col(RefreshTokens::ExpiresAt).to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefreshTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum RefreshTokens {
+    Table,
+    Id,
+    UserId,
+    FamilyId,
+    TokenHash,
+    ExpiresAt,
+    RevokedAt,
+    CreatedAt,
+}
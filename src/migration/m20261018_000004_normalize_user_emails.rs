@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use super::create_index_if_missing;
+use super::m20261018_000001_create_users_table::Users;
+
+const INDEX: &str = "uk_users_email";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// 已有数据中的邮箱统一转小写后再建唯一索引；仅大小写或空白不同的重复邮箱需要先人工合并
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let rows = db
+            .query_all(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT id, LOWER(TRIM(email)) AS email FROM users \
+                 WHERE LOWER(TRIM(email)) IN \
+                 (SELECT LOWER(TRIM(email)) FROM users GROUP BY LOWER(TRIM(email)) HAVING COUNT(*) > 1) \
+                 ORDER BY email, id",
+            ))
+            .await?;
+        if !rows.is_empty() {
+            let mut conflicts: Vec<(String, Vec<i32>)> = Vec::new();
+            for row in rows {
+                let id: i32 = row.try_get("", "id")?;
+                let email: String = row.try_get("", "email")?;
+                match conflicts.last_mut() {
+                    Some((last, ids)) if *last == email => ids.push(id),
+                    _ => conflicts.push((email, vec![id])),
+                }
+            }
+            let list: Vec<String> = conflicts
+                .iter()
+                .map(|(email, ids)| format!("  {} (用户 id: {:?})", email, ids))
+                .collect();
+            return Err(DbErr::Migration(format!(
+                "以下邮箱转小写后重复，请先人工合并这些用户再执行迁移:\n{}",
+                list.join("\n")
+            )));
+        }
+
+        db.execute_unprepared("UPDATE users SET email = LOWER(TRIM(email))").await?;
+
+        create_index_if_missing(
+            manager,
+            Users::Table,
+            INDEX,
+            Index::create().col(Users::Email).unique().to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 原始大小写无法恢复，只删除唯一索引
+        manager
+            .drop_index(Index::drop().name(INDEX).table(Users::Table).to_owned())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm_migration::sea_orm::{ConnectOptions, Database};
+    use sea_orm_migration::MigratorTrait;
+
+    use super::*;
+    use crate::migration::Migrator;
+
+    #[tokio::test]
+    async fn refuses_emails_that_collide_after_lowercasing() {
+        let mut options = ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1).min_connections(1).sqlx_logging(false);
+        let db = Database::connect(options).await.unwrap();
+        Migrator::up(&db, Some(3)).await.unwrap();
+        db.execute_unprepared(
+            "INSERT INTO users (name, email, password, role, created_at, updated_at) VALUES \
+             ('a', 'Bob@Example.com', 'x', 'user', '2026-01-01', '2026-01-01'), \
+             ('b', ' bob@example.com', 'x', 'user', '2026-01-01', '2026-01-01'), \
+             ('c', 'Carol@Example.com', 'x', 'user', '2026-01-01', '2026-01-01')",
+        )
+        .await
+        .unwrap();
+
+        let err = Migrator::up(&db, None).await.unwrap_err().to_string();
+        assert!(err.contains("bob@example.com (用户 id: [1, 2])"), "{}", err);
+        assert!(!err.contains("carol"), "{}", err);
+
+        // 合并重复用户后迁移可以继续，其余邮箱被转成小写
+        db.execute_unprepared("DELETE FROM users WHERE id = 2").await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        let row = db
+            .query_one(Statement::from_string(db.get_database_backend(), "SELECT email FROM users WHERE id = 3"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.try_get::<String>("", "email").unwrap(), "carol@example.com");
+    }
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20261018_000001_create_users_table::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(DeletedAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_users_deleted_at")
+                    .table(Users::Table)
+                    .col(DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_users_deleted_at").table(Users::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(Table::alter().table(Users::Table).drop_column(DeletedAt).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+struct DeletedAt;
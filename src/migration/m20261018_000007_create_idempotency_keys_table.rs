@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_index_if_missing;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdempotencyKeys::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(IdempotencyKeys::KeyHash).string_len(64).not_null().primary_key())
+                    .col(ColumnDef::new(IdempotencyKeys::RequestHash).string_len(64).not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::StatusCode).integer().null())
+                    .col(ColumnDef::new(IdempotencyKeys::ContentType).string_len(255).null())
+                    .col(ColumnDef::new(IdempotencyKeys::ResponseBody).text().null())
+                    .col(ColumnDef::new(IdempotencyKeys::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::ExpiresAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::LockedUntil).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        create_index_if_missing(
+            manager,
+            IdempotencyKeys::Table,
+            "idx_idempotency_keys_expires_at",
+            Index::create().col(IdempotencyKeys::ExpiresAt).to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IdempotencyKeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum IdempotencyKeys {
+    Table,
+    KeyHash,
+    RequestHash,
+    StatusCode,
+    ContentType,
+    ResponseBody,
+    CreatedAt,
+    ExpiresAt,
+    LockedUntil,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_index_if_missing;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLogs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLogs::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuditLogs::OccurredAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(AuditLogs::ActorId).integer().null())
+                    .col(ColumnDef::new(AuditLogs::Actor).string_len(255).null())
+                    .col(ColumnDef::new(AuditLogs::Action).string_len(64).not_null())
+                    .col(ColumnDef::new(AuditLogs::TargetType).string_len(32).null())
+                    .col(ColumnDef::new(AuditLogs::TargetId).string_len(64).null())
+                    .col(ColumnDef::new(AuditLogs::Ip).string_len(64).null())
+                    .col(ColumnDef::new(AuditLogs::UserAgent).string_len(512).null())
+                    .col(ColumnDef::new(AuditLogs::Outcome).string_len(16).not_null())
+                    .col(ColumnDef::new(AuditLogs::Reason).string_len(255).null())
+                    .col(ColumnDef::new(AuditLogs::Changes).json().null())
+                    .col(ColumnDef::new(AuditLogs::RequestId).string_len(128).null())
+                    .to_owned(),
+            )
+            .await?;
+
+        for (name, column) in [
+            ("idx_audit_logs_occurred_at", AuditLogs::OccurredAt),
+            ("idx_audit_logs_actor_id", AuditLogs::ActorId),
+            ("idx_audit_logs_action", AuditLogs::Action),
+        ] {
+            create_index_if_missing(manager, AuditLogs::Table, name, Index::create().col(column).to_owned()).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLogs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLogs {
+    Table,
+    Id,
+    OccurredAt,
+    ActorId,
+    Actor,
+    Action,
+    TargetType,
+    TargetId,
+    Ip,
+    UserAgent,
+    Outcome,
+    Reason,
+    Changes,
+    RequestId,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+mod m20261018_000001_create_users_table;
+mod m20261018_000002_create_refresh_tokens_table;
+mod m20261018_000003_create_roles_table;
+mod m20261018_000004_normalize_user_emails;
+mod m20261018_000005_add_users_deleted_at;
+mod m20261018_000006_add_users_version;
+mod m20261018_000007_create_idempotency_keys_table;
+mod m20261018_000008_create_revoked_tokens_table;
+mod m20261018_000009_create_audit_logs_table;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261018_000001_create_users_table::Migration),
+            Box::new(m20261018_000002_create_refresh_tokens_table::Migration),
+            Box::new(m20261018_000003_create_roles_table::Migration),
+            Box::new(m20261018_000004_normalize_user_emails::Migration),
+            Box::new(m20261018_000005_add_users_deleted_at::Migration),
+            Box::new(m20261018_000006_add_users_version::Migration),
+            Box::new(m20261018_000007_create_idempotency_keys_table::Migration),
+            Box::new(m20261018_000008_create_revoked_tokens_table::Migration),
+            Box::new(m20261018_000009_create_audit_logs_table::Migration),
+        ]
+    }
+}
+
+// 索引不存在时才创建；MySQL 不支持 CREATE INDEX IF NOT EXISTS，统一先查询，
+// 使 if_not_exists 建表之后的索引也能在已有表上重复执行
+async fn create_index_if_missing<T>(
+    manager: &SchemaManager<'_>,
+    table: T,
+    name: &str,
+    mut index: IndexCreateStatement,
+) -> Result<(), DbErr>
+where
+    T: Iden + 'static,
+{
+    if manager.has_index(table.to_string(), name).await? {
+        return Ok(());
+    }
+    manager.create_index(index.name(name).table(table).to_owned()).await
+}
+
+// migrate 子命令: migrate up|down|status
+pub async fn run_command(db: &sea_orm::DatabaseConnection, command: Option<&str>) -> Result<(), DbErr> {
+    match command.unwrap_or("status") {
+        "up" => {
+            Migrator::up(db, None).await?;
+            println!("迁移已全部执行");
+        }
+        "down" => {
+            Migrator::down(db, Some(1)).await?;
+            println!("已回滚最近一次迁移");
+        }
+        "status" => {
+            for migration in Migrator::get_migration_with_status(db).await? {
+                println!("{:<50} {}", migration.name(), migration.status());
+            }
+        }
+        other => {
+            return Err(DbErr::Custom(format!("未知的迁移命令: {}，可用命令: up | down | status", other)));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+
+use crate::config::PasswordConfig;
+
+#[derive(Debug)]
+pub struct PasswordError {
+    pub message: String,
+}
+
+impl std::fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PasswordError {}
+
+// 可插拔的密码哈希算法
+pub trait PasswordHasher: Send + Sync {
+    fn hash(&self, password: &str) -> Result<String, PasswordError>;
+
+    // 存储的哈希不是当前算法或参数生成的，登录成功后需要重新哈希
+    fn needs_rehash(&self, stored_hash: &str) -> bool;
+}
+
+pub struct BcryptHasher {
+    pub cost: u32,
+}
+
+impl PasswordHasher for BcryptHasher {
+    #[tracing::instrument(name = "bcrypt.hash", skip_all, fields(cost = self.cost))]
+    fn hash(&self, password: &str) -> Result<String, PasswordError> {
+        bcrypt::hash(password, self.cost).map_err(|e| PasswordError {
+            message: e.to_string(),
+        })
+    }
+
+    fn needs_rehash(&self, stored_hash: &str) -> bool {
+        // bcrypt 格式: $2b$<cost>$<salt+hash>
+        match stored_hash.split('$').collect::<Vec<_>>().as_slice() {
+            ["", "2a" | "2b" | "2x" | "2y", cost, _] => cost.parse::<u32>().ok() != Some(self.cost),
+            _ => true,
+        }
+    }
+}
+
+pub struct Argon2idHasher {
+    params: Params,
+}
+
+impl Argon2idHasher {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self, PasswordError> {
+        let params = Params::new(memory_kib, iterations, parallelism, None).map_err(|e| PasswordError {
+            message: e.to_string(),
+        })?;
+        Ok(Argon2idHasher { params })
+    }
+
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
+impl PasswordHasher for Argon2idHasher {
+    #[tracing::instrument(name = "argon2.hash", skip_all)]
+    fn hash(&self, password: &str) -> Result<String, PasswordError> {
+        let salt = SaltString::generate(&mut OsRng);
+        self.argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| PasswordError {
+                message: e.to_string(),
+            })
+    }
+
+    fn needs_rehash(&self, stored_hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(stored_hash) else {
+            return true;
+        };
+        if parsed.algorithm != Algorithm::Argon2id.ident() {
+            return true;
+        }
+        match Params::try_from(&parsed) {
+            Ok(params) => {
+                params.m_cost() != self.params.m_cost()
+                    || params.t_cost() != self.params.t_cost()
+                    || params.p_cost() != self.params.p_cost()
+            }
+            Err(_) => true,
+        }
+    }
+}
+
+// 按哈希前缀识别算法并校验，兼容所有历史格式
+pub fn verify(stored_hash: &str, input_password: &str) -> bool {
+    if stored_hash.starts_with("$argon2") {
+        let _span = tracing::info_span!("argon2.verify").entered();
+        PasswordHash::new(stored_hash)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(input_password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    } else {
+        let _span = tracing::info_span!("bcrypt.verify").entered();
+        bcrypt::verify(input_password, stored_hash).unwrap_or(false)
+    }
+}
+
+// 哈希和校验是 CPU 密集操作，放到阻塞线程池执行，避免占住 tokio 工作线程；
+// 带上当前 span，让 bcrypt/argon2 的 span 仍挂在调用方下面
+async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+pub async fn hash_blocking(hasher: Arc<dyn PasswordHasher>, password: String) -> Result<String, PasswordError> {
+    run_blocking(move || hasher.hash(&password)).await
+}
+
+pub async fn verify_blocking(stored_hash: String, input_password: String) -> bool {
+    run_blocking(move || verify(&stored_hash, &input_password)).await
+}
+
+// 根据配置选择当前使用的哈希算法，默认 bcrypt
+pub fn hasher_from_config(config: &PasswordConfig) -> Result<Arc<dyn PasswordHasher>, PasswordError> {
+    match config.hasher.to_lowercase().as_str() {
+        "bcrypt" => Ok(Arc::new(BcryptHasher {
+            cost: config.bcrypt_cost,
+        })),
+        "argon2id" | "argon2" => Ok(Arc::new(Argon2idHasher::new(
+            config.argon2_memory_kib,
+            config.argon2_iterations,
+            config.argon2_parallelism,
+        )?)),
+        other => Err(PasswordError {
+            message: format!("不支持的密码哈希算法: {}", other),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcrypt_needs_rehash_on_cost_or_algorithm_change() {
+        let hasher = BcryptHasher { cost: 10 };
+        assert!(!hasher.needs_rehash("$2b$10$abcdefghijklmnopqrstuu"));
+        assert!(!hasher.needs_rehash("$2y$10$abcdefghijklmnopqrstuu"));
+        assert!(hasher.needs_rehash("$2b$12$abcdefghijklmnopqrstuu"));
+        assert!(hasher.needs_rehash("$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA"));
+        assert!(hasher.needs_rehash("plain"));
+    }
+
+    #[test]
+    fn verify_accepts_bcrypt_hashes() {
+        let hash = BcryptHasher { cost: 4 }.hash("secret").unwrap();
+        assert!(verify(&hash, "secret"));
+        assert!(!verify(&hash, "other"));
+        assert!(!verify("", "secret"));
+    }
+}
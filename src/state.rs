@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sea_orm::DatabaseConnection;
+
+use crate::audit::AuditLog;
+use crate::config::Config;
+use crate::database::{IdempotencyStore, SeaOrmIdempotencyStore, SeaOrmUserRepository, UserRepository};
+use crate::health::{DatabaseCheck, HealthRegistry, MigrationCheck};
+use crate::middleware::jwt_keys::JwtKeys;
+use crate::middleware::rate_limit::{InMemoryRateLimitStore, LockoutPolicy, RateLimitStore};
+use crate::password::PasswordHasher;
+use crate::shutdown::Readiness;
+
+// 应用共享状态，启动时构建一次，通过 Router::with_state 注入到各个路由
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Config>,
+    pub db: DatabaseConnection,
+    pub users: Arc<dyn UserRepository>,
+    pub idempotency: Arc<dyn IdempotencyStore>,
+    pub hasher: Arc<dyn PasswordHasher>,
+    // 用当前算法和参数生成的占位哈希，邮箱不存在时同样校验一次，使耗时与密码错误一致
+    pub dummy_hash: Arc<str>,
+    pub rate_limiter: Arc<dyn RateLimitStore>,
+    pub lockout: LockoutPolicy,
+    pub jwt: Arc<JwtKeys>,
+    pub ready: Readiness,
+    pub health: Arc<HealthRegistry>,
+    pub audit: Arc<AuditLog>,
+}
+
+impl AppState {
+    pub fn new(
+        config: Config,
+        db: DatabaseConnection,
+        hasher: Arc<dyn PasswordHasher>,
+        jwt: JwtKeys,
+        audit: AuditLog,
+    ) -> Self {
+        let mut health = HealthRegistry::new(Duration::from_millis(config.server.health_check_timeout_ms));
+        health.register(Arc::new(DatabaseCheck::new(db.clone())));
+        health.register(Arc::new(MigrationCheck::new(db.clone())));
+
+        let dummy_hash = hasher.hash("dummy-password").unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "生成占位密码哈希失败");
+            String::new()
+        });
+
+        AppState {
+            dummy_hash: dummy_hash.into(),
+            lockout: LockoutPolicy::from_config(&config.login),
+            config: Arc::new(config),
+            users: Arc::new(SeaOrmUserRepository::new(db.clone())),
+            idempotency: Arc::new(SeaOrmIdempotencyStore::new(db.clone())),
+            db,
+            hasher,
+            rate_limiter: Arc::new(InMemoryRateLimitStore::default()),
+            jwt: Arc::new(jwt),
+            ready: Readiness::default(),
+            health: Arc::new(health),
+            audit: Arc::new(audit),
+        }
+    }
+}
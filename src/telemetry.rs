@@ -0,0 +1,205 @@
+use axum::http::HeaderMap;
+use tracing::Span;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+use crate::config::TelemetryConfig;
+
+pub type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+// OTLP 链路导出，未以 otel feature 编译或未启用时为空操作
+#[derive(Default)]
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Telemetry {
+    // 构建导出 span 的 tracing layer，filter 与日志使用相同的指令
+    pub fn init(
+        config: &TelemetryConfig,
+        filter: EnvFilter,
+    ) -> Result<(Self, Option<BoxedLayer>), Box<dyn std::error::Error + Send + Sync>> {
+        if !config.enabled {
+            return Ok((Telemetry::default(), None));
+        }
+        #[cfg(feature = "otel")]
+        {
+            let provider = otel::provider(config)?;
+            let layer = otel::layer(&provider).with_filter(filter).boxed();
+            Ok((Telemetry { provider: Some(provider) }, Some(layer)))
+        }
+        // 配置校验已拒绝这种组合
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = filter;
+            Ok((Telemetry::default(), None))
+        }
+    }
+
+    // 退出前导出缓冲中的 span
+    pub fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!(error = %e, "关闭链路导出失败");
+            }
+        }
+    }
+}
+
+// 按 W3C traceparent 请求头把 span 挂到上游链路下
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    #[cfg(feature = "otel")]
+    otel::set_parent(span, headers);
+    #[cfg(not(feature = "otel"))]
+    let _ = (span, headers);
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use std::time::Duration;
+
+    use axum::http::HeaderMap;
+    use opentelemetry::propagation::{Extractor, TextMapPropagator};
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{Builder, Sampler, TracerProvider};
+    use opentelemetry_sdk::{runtime, Resource};
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::{Layer, Registry};
+
+    use crate::config::TelemetryConfig;
+
+    pub fn provider(config: &TelemetryConfig) -> Result<TracerProvider, opentelemetry::trace::TraceError> {
+        let timeout = Duration::from_secs(config.export_timeout_secs);
+        let exporter = if config.protocol == "http" {
+            let endpoint = format!("{}/v1/traces", config.endpoint.trim_end_matches('/'));
+            SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .with_timeout(timeout)
+                .build()?
+        } else {
+            SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(config.endpoint.clone())
+                .with_timeout(timeout)
+                .build()?
+        };
+
+        let builder = TracerProvider::builder().with_batch_exporter(exporter, runtime::Tokio);
+        Ok(configure(builder, config).build())
+    }
+
+    // 采样和资源属性与导出方式无关，测试中搭配替身导出器复用
+    fn configure(builder: Builder, config: &TelemetryConfig) -> Builder {
+        builder
+            .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sample_ratio))))
+            .with_resource(Resource::new([KeyValue::new("service.name", config.service_name.clone())]))
+    }
+
+    pub fn layer(provider: &TracerProvider) -> impl Layer<Registry> + Send + Sync {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("test2"))
+    }
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    pub fn set_parent(span: &Span, headers: &HeaderMap) {
+        let context = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+        span.set_parent(context);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::{Arc, Mutex};
+
+        use futures::future::BoxFuture;
+        use opentelemetry::trace::{SpanId, TraceId};
+        use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+        use sea_orm::{ConnectOptions, Database};
+        use sea_orm_migration::MigratorTrait;
+        use tracing::Instrument;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        use super::*;
+        use crate::database::mysql_orm;
+        use crate::migration::Migrator;
+        use crate::password::BcryptHasher;
+
+        // 把导出的 span 收集到内存中
+        #[derive(Debug, Clone, Default)]
+        struct CollectingExporter(Arc<Mutex<Vec<SpanData>>>);
+
+        impl SpanExporter for CollectingExporter {
+            fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+                self.0.lock().unwrap().extend(batch);
+                Box::pin(async { Ok(()) })
+            }
+        }
+
+        #[tokio::test]
+        async fn spans_follow_traceparent_and_nest_database_and_hashing() {
+            let mut options = ConnectOptions::new("sqlite::memory:");
+            options.max_connections(1).sqlx_logging(false);
+            let db = Database::connect(options).await.unwrap();
+            Migrator::up(&db, None).await.unwrap();
+
+            // 采样率为 0 时，上游已采样的链路仍然保留
+            let config = TelemetryConfig {
+                sample_ratio: 0.0,
+                ..Default::default()
+            };
+            let exporter = CollectingExporter::default();
+            let provider = configure(TracerProvider::builder().with_simple_exporter(exporter.clone()), &config).build();
+            // SQLite 在独立线程上执行语句，span 在该线程退出时按全局订阅者关闭，所以不能只设置线程内默认值
+            tracing::subscriber::set_global_default(Registry::default().with(layer(&provider))).unwrap();
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".parse().unwrap(),
+            );
+            let request = tracing::info_span!("request");
+            set_parent(&request, &headers);
+            let hasher = std::sync::Arc::new(BcryptHasher { cost: 4 });
+            mysql_orm::create_user(&db, hasher, "a".into(), "a@example.com".into(), "secret".into())
+                .instrument(request)
+                .await
+                .unwrap();
+            // 工作线程处理完关闭命令时，之前语句所在的 span 都已释放
+            db.close().await.unwrap();
+            provider.force_flush();
+
+            // 全局订阅者也会收到并行测试的 span，只看本链路
+            let trace_id = TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap();
+            let spans = exporter.0.lock().unwrap();
+            let find = |name: &str| {
+                spans
+                    .iter()
+                    .find(|s| s.name == name && s.span_context.trace_id() == trace_id)
+                    .unwrap_or_else(|| panic!("缺少 span {}", name))
+            };
+            let (request, create, hash) = (find("request"), find("mysql_orm.create_user"), find("bcrypt.hash"));
+
+            assert_eq!(request.parent_span_id, SpanId::from_hex("00f067aa0ba902b7").unwrap());
+            for span in [request, create, hash] {
+                assert!(span.span_context.is_sampled(), "{}", span.name);
+            }
+            assert_eq!(create.parent_span_id, request.span_context.span_id());
+            assert_eq!(hash.parent_span_id, create.span_context.span_id());
+        }
+    }
+}
@@ -1,4 +1,3 @@
-use std::path::Path;
 use std::sync::Arc;
 use tokio::{fs::File, io::{AsyncReadExt, AsyncWriteExt, AsyncSeekExt}, sync::{Semaphore, mpsc}};
 
@@ -1,5 +1,4 @@
 use tokio::sync::mpsc;
-use std::sync::Arc;
 
 const CHUNK_SIZE: usize = 10000;
 const MAX_CONCURRENT: usize = 10;
@@ -44,10 +44,10 @@ pub async fn test_sephone() {
 
 async fn async_range(from:i32, to:i32) -> impl tokio_stream::Stream<Item = i32>{
     println!("从{} 到 {}", from, to);
-    tokio_stream::iter((from..to).map(|i| i))
+    tokio_stream::iter(from..to)
 }
 
-pub async fn test_streamExt() {
+pub async fn test_stream_ext() {
     let mut stream = async_range(1,5).await;
     while let Some(i) = stream.next().await {
         println!("获取到值：{}", i);
@@ -71,7 +71,7 @@ async fn with_timeout_and_retry() -> Result<i32, &'static str> {
                 return result;
             }
             Err(_) => {
-                println!("操作超时，准备重试...");
+                println!("第{}次操作超时，准备重试...", attempt);
             }
         }
     }
@@ -91,7 +91,7 @@ pub async fn retry_timeout() {
     }
 }
 
-fn process_data(data: &mut Vec<i32>) {
+fn process_data(data: &mut [i32]) {
     // 处理数据
     data.iter().for_each(|&value| {
         println!("Processing value: {}", value);
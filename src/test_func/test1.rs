@@ -44,6 +44,8 @@ pub fn test_static() {
     println!("{}", static_str);
 }
 
+// 这里要测的就是 while let 写法本身
+#[allow(clippy::while_let_on_iterator)]
 pub fn test_slice() {
     // 测试while let与for的性能差异
     let v: Vec<_> = (0..1_000_000).collect();
@@ -56,5 +58,5 @@ pub fn test_slice() {
         sum += n;
     }
     let duration = start.elapsed();  // 获取耗时
-    println!("耗时: {:?}", duration);
+    println!("和: {}, 耗时: {:?}", sum, duration);
 }
\ No newline at end of file